@@ -0,0 +1,182 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Fields, GenericParam,
+    Ident, LitStr,
+};
+
+/// Implement `gerning::service::State` for a struct with named fields.
+///
+/// Fields are looked up by name and converted to and from the value type with
+/// `Into<V>` and `TryFrom<V>`. Use `#[state(rename = "name")]` to expose a field under
+/// another name and `#[state(skip)]` to hide it.
+///
+/// Setting a field to a value that does not convert fails with `ArgumentError::IvalidType`,
+/// in a `Frame::Message` naming the field.
+#[proc_macro_derive(State, attributes(state))]
+pub fn derive_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(ret) => ret.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct Field {
+    ident: Ident,
+    name: LitStr,
+    ty: syn::Type,
+}
+
+fn fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "State can only be derived for structs",
+        ));
+    };
+
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "State can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut fields = Vec::with_capacity(named.named.len());
+
+    for field in &named.named {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = LitStr::new(&ident.to_string(), ident.span());
+        let mut skip = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("state"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown state attribute"))
+                }
+            })?;
+        }
+
+        if !skip {
+            fields.push(Field {
+                ident,
+                name,
+                ty: field.ty.clone(),
+            });
+        }
+    }
+
+    Ok(fields)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(&input)?;
+    let name = &input.ident;
+    let value = Ident::new("__V", Span::call_site());
+
+    let mut generics = input.generics.clone();
+    generics
+        .params
+        .push(GenericParam::Type(parse_quote!(#value: ::gerning::Value)));
+
+    {
+        let where_clause = generics.make_where_clause();
+        for field in &fields {
+            let ty = &field.ty;
+            where_clause.predicates.push(parse_quote!(
+                #ty: ::core::clone::Clone
+                    + ::core::convert::Into<#value>
+                    + ::core::convert::TryFrom<#value>
+                    + ::gerning::Typed<#value>
+            ));
+        }
+    }
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let get = fields.iter().map(|field| {
+        let Field { ident, name, .. } = field;
+        quote! {
+            #name => ::core::result::Result::Ok(::core::option::Option::Some(
+                ::core::convert::Into::into(::core::clone::Clone::clone(&self.#ident)),
+            )),
+        }
+    });
+
+    let set = fields.iter().map(|field| {
+        let Field { ident, name, ty } = field;
+        quote! {
+            #name => {
+                let found = ::gerning::Value::get_type(&value);
+                // The conversion error is not bounded, so the mismatch is reported by type
+                self.#ident = <#ty as ::core::convert::TryFrom<#value>>::try_from(value).map_err(|_| {
+                    ::gerning::Error::Argument(::gerning::arguments::ArgumentError::IvalidType {
+                        expected: <#ty as ::gerning::Typed<#value>>::get_type(),
+                        found,
+                    })
+                    .context(::core::concat!("field `", #name, "`"))
+                })?;
+                ::core::result::Result::Ok(())
+            }
+        }
+    });
+
+    let introspect = fields.iter().map(|field| {
+        let Field { name, ty, .. } = field;
+        quote! {
+            ::gerning::service::StateField {
+                name: #name,
+                ty: <#ty as ::gerning::Typed<#value>>::get_type(),
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::gerning::service::State<#value> for #name #ty_generics #where_clause {
+            fn get(
+                &self,
+                name: &str,
+            ) -> ::core::result::Result<::core::option::Option<#value>, ::gerning::Error<#value>> {
+                match name {
+                    #(#get)*
+                    _ => ::core::result::Result::Err(::gerning::Error::UnknownField(
+                        ::core::convert::Into::into(name),
+                    )),
+                }
+            }
+
+            #[allow(unused_variables)]
+            fn set(
+                &mut self,
+                name: &str,
+                value: #value,
+            ) -> ::core::result::Result<(), ::gerning::Error<#value>> {
+                match name {
+                    #(#set)*
+                    _ => ::core::result::Result::Err(::gerning::Error::UnknownField(
+                        ::core::convert::Into::into(name),
+                    )),
+                }
+            }
+        }
+
+        impl #impl_generics ::gerning::service::StateFields<#value> for #name #ty_generics #where_clause {
+            fn fields() -> ::gerning::__private::vec::Vec<::gerning::service::StateField<#value>> {
+                ::gerning::__private::vec![#(#introspect),*]
+            }
+        }
+    })
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gerning::{
+    arguments::{ArgumentError, Arguments, ToArguments},
+    service::{DynService, Service, SyncState},
+    Callable, Error, FuncExt,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Value {
+    Int(i64),
+    #[default]
+    Void,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int,
+    Void,
+}
+
+impl gerning::Value for Value {
+    type Type = Type;
+
+    fn get_type(&self) -> Self::Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::Void => Type::Void,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for i64 {
+    type Error = ArgumentError<Value>;
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int(i) => Ok(*i),
+            v => Err(ArgumentError::IvalidType {
+                expected: Type::Int,
+                found: gerning::Value::get_type(v),
+            }),
+        }
+    }
+}
+
+impl gerning::Typed<Value> for i64 {
+    fn get_type() -> Type {
+        Type::Int
+    }
+}
+
+fn zero(_ctx: &mut ()) -> i64 {
+    0
+}
+
+fn one(_ctx: &mut (), a: i64) -> i64 {
+    a
+}
+
+fn three(_ctx: &mut (), a: i64, b: i64, c: i64) -> i64 {
+    a + b + c
+}
+
+fn to_arguments(c: &mut Criterion) {
+    fn args<T: ToArguments<Value>>(args: T) -> Arguments<Value> {
+        args.to_arguments()
+    }
+
+    let mut group = c.benchmark_group("to_arguments");
+
+    group.bench_function("0", |b| b.iter(|| args(())));
+    group.bench_function("1", |b| b.iter(|| args(black_box((1i64,)))));
+    group.bench_function("3", |b| b.iter(|| args(black_box((1i64, 2i64, 3i64)))));
+    group.bench_function("8", |b| {
+        b.iter(|| args(black_box((1i64, 2i64, 3i64, 4i64, 5i64, 6i64, 7i64, 8i64))))
+    });
+
+    group.finish();
+}
+
+fn callable_func(c: &mut Criterion) {
+    let mut group = c.benchmark_group("callable_func");
+
+    let zero = zero.callable::<Value>();
+    group.bench_function("0", |b| b.iter(|| zero.call(&mut (), ().to_arguments())));
+
+    let one = one.callable::<Value>();
+    group.bench_function("1", |b| {
+        b.iter(|| one.call(&mut (), black_box((1i64,)).to_arguments()))
+    });
+
+    let three = three.callable::<Value>();
+    group.bench_function("3", |b| {
+        b.iter(|| three.call(&mut (), black_box((1i64, 2i64, 3i64)).to_arguments()))
+    });
+
+    group.finish();
+}
+
+fn dyn_service(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dyn_service");
+
+    let mut service = DynService::new(SyncState::new(BTreeMap::<String, Value>::default()));
+
+    service.register(
+        "zero",
+        |_this: &mut BTreeMap<String, Value>, _ctx: &mut (), _args: Arguments<Value>| {
+            Ok::<_, Error<Value>>(0i64)
+        },
+    );
+
+    service.register(
+        "three",
+        |_this: &mut BTreeMap<String, Value>, _ctx: &mut (), args: Arguments<Value>| {
+            let a: i64 = args.try_get_ref(0)?;
+            let b: i64 = args.try_get_ref(1)?;
+            let c: i64 = args.try_get_ref(2)?;
+            Ok::<_, Error<Value>>(a + b + c)
+        },
+    );
+
+    // Pad the method table, lookups are part of the dispatch cost
+    for idx in 0..32 {
+        service.register(
+            &format!("method_{idx}"),
+            |_this: &mut BTreeMap<String, Value>, _ctx: &mut (), _args: Arguments<Value>| {
+                Ok::<_, Error<Value>>(0i64)
+            },
+        );
+    }
+
+    group.bench_function("0", |b| {
+        b.iter(|| service.call(&mut (), black_box("zero"), ().to_arguments()))
+    });
+
+    group.bench_function("3", |b| {
+        b.iter(|| {
+            service.call(
+                &mut (),
+                black_box("three"),
+                black_box((1i64, 2i64, 3i64)).to_arguments(),
+            )
+        })
+    });
+
+    let id = service.resolve("three").expect("three");
+    group.bench_function("3_by_id", |b| {
+        b.iter(|| {
+            service.call_by_id(
+                &mut (),
+                black_box(&id),
+                black_box((1i64, 2i64, 3i64)).to_arguments(),
+            )
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, to_arguments, callable_func, dyn_service);
+criterion_main!(benches);
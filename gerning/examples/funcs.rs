@@ -1,13 +1,10 @@
-use std::{collections::BTreeMap, convert::Infallible};
+use std::collections::BTreeMap;
 
 use futures_core::Future;
 use gerning::{
-    arguments::{Arguments, ToArguments},
-    service::{
-        AsyncMethodCallable, AsyncMethodCallableExt, AsyncService, AsyncState, SendState, Service,
-        State, SyncState,
-    },
-    AsyncCallable, AsyncCallableExt, AsyncFunc, Callable, CallableFunc, Error, Func, FuncExt,
+    arguments::{ArgumentError, Arguments, BorrowArgument, ToArguments},
+    service::{AsyncMethodCallable, AsyncService, AsyncState, Service, State, SyncState},
+    AsyncCallable, AsyncCallableExt, Callable, CallableFunc, Error, FuncExt,
 };
 
 #[derive(Debug, Clone)]
@@ -29,7 +26,7 @@ impl From<String> for Value {
 }
 
 impl From<()> for Value {
-    fn from(value: ()) -> Self {
+    fn from(_: ()) -> Self {
         Value::Void
     }
 }
@@ -59,27 +56,26 @@ impl TryFrom<Value> for String {
 }
 
 impl<'a> TryFrom<&'a Value> for String {
-    type Error = Infallible;
+    type Error = ArgumentError<Value>;
     fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
-        match value {
-            Value::String(s) => Ok(s.clone()),
-            _ => {
-                panic!()
-            }
-        }
+        str::borrow_argument(value).map(String::from)
     }
 }
 
-impl<'a> TryFrom<&'a Value> for &'a str {
-    type Error = Infallible;
-    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+impl BorrowArgument<Value> for str {
+    fn borrow_argument(value: &Value) -> Result<&Self, ArgumentError<Value>> {
         match value {
             Value::String(s) => Ok(s),
-            _ => {
-                panic!()
-            }
+            v => Err(ArgumentError::IvalidType {
+                expected: Type::String,
+                found: gerning::Value::get_type(v),
+            }),
         }
     }
+
+    fn parameter_type() -> Type {
+        Type::String
+    }
 }
 
 impl gerning::Value for Value {
@@ -111,22 +107,12 @@ impl gerning::Typed<Value> for Value {
     }
 }
 
-impl<'a> gerning::Typed<Value> for &'a str {
-    fn get_type() -> <Value as gerning::Value>::Type {
-        Type::String
-    }
-}
-
-fn test<'a, C>(ctx: &'a mut C, _test: String) -> Result<String, Error<Value>> {
+fn test<C>(_ctx: &mut C, _test: String) -> Result<String, Error<Value>> {
     Ok(String::from("Test func"))
 }
 
-async fn test_async(ctx: &mut (), _test: String) -> Result<String, Error<Value>> {
-    Ok(String::from("Test func"))
-}
-
-async fn method<F: AsyncFunc<(), (String,)>>(func: F) {
-    func.call(&mut (), (String::from("value"),)).await;
+fn greet(_ctx: &mut (), name: &str) -> String {
+    format!("Hello, {name}")
 }
 
 struct TestService;
@@ -153,7 +139,7 @@ where
     //     todo!()
     // }
 
-    fn call(&self, ctx: &mut C, name: &str, args: Arguments<V>) -> Result<V, Error<V>> {
+    fn call(&self, _ctx: &mut C, name: &str, _args: Arguments<V>) -> Result<V, Error<V>> {
         match name {
             "test" => Ok(self.test().into()),
             _ => Err(Error::MethodNotFound),
@@ -174,25 +160,40 @@ fn main() -> Result<(), Error<Value>> {
     let ret = callable.call(&mut (), ("",).to_arguments())?;
 
     println!("RET: {:?}", ret);
-    let action = CallableFunc::new(|ctx: &mut (), person: String| {
+
+    // Borrowed parameters point into the arguments instead of copying them
+    let greet = greet.callable::<Value>();
+    let ret = greet.call(&mut (), ("World",).to_arguments())?;
+    println!("RET: {:?}", ret);
+
+    let length = (|_ctx: &mut (), name: &str| {
+        let len = name.len();
+        to_send(async move { Result::<_, Error<Value>>::Ok(len.to_string()) })
+    })
+    .callable::<Value>();
+    let ret = futures::executor::block_on(length.call_async(&mut (), ("World",).to_arguments()))?;
+    println!("RET: {:?}", ret);
+
+    let action = CallableFunc::new(|_ctx: &mut (), person: String| {
         to_send(async move { Result::<_, Error<Value>>::Ok(format!("Hello, {}", person)) })
     })
     .boxed();
 
-    action.call_async(&mut (), Arguments::default());
+    let ret = futures::executor::block_on(action.call_async(&mut (), ("World",).to_arguments()))?;
+    println!("RET: {:?}", ret);
 
     let mut service = gerning::service::DynService::new(SyncState::new(BTreeMap::default()));
 
     service.register(
         "test",
-        |this: &mut BTreeMap<String, Value>, ctx: &mut (), args: Arguments<Value>| {
+        |this: &mut BTreeMap<String, Value>, _ctx: &mut (), _args: Arguments<Value>| {
             this.get("state").cloned().ok_or_else(|| Error::Infallible)
         },
     );
 
     service.register(
         "set_test",
-        |this: &mut BTreeMap<String, Value>, ctx: &mut (), args: Arguments<Value>| {
+        |this: &mut BTreeMap<String, Value>, _ctx: &mut (), args: Arguments<Value>| {
             this.set("state", args.get(0).cloned().unwrap())?;
             Ok::<_, Error<_>>(())
         },
@@ -276,14 +277,14 @@ impl<S: State<Value>, C> AsyncMethodCallable<S, C, Value> for TestAsync {
         S: 'a;
 
     fn signature(&self) -> gerning::signature::Signature<Value> {
-        todo!()
+        gerning::signature::Signature::new(Default::default(), Type::String)
     }
 
     fn call_async<'a>(
         &'a self,
         this: &'a mut S,
-        ctx: &'a mut C,
-        args: Arguments<Value>,
+        _ctx: &'a mut C,
+        _args: Arguments<Value>,
     ) -> Self::Future<'a> {
         core::future::ready(Ok(this.get("state").unwrap().unwrap()))
     }
@@ -0,0 +1,159 @@
+use std::convert::Infallible;
+
+use gerning::{
+    arguments::{ArgumentError, ToArguments},
+    no_context, AsyncCallable, Callable, CallableExt, Error, FuncExt, Thread,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Void,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int,
+    Void,
+}
+
+impl gerning::Value for Value {
+    type Type = Type;
+
+    fn get_type(&self) -> Self::Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::Void => Type::Void,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for i64 {
+    type Error = ArgumentError<Value>;
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int(i) => Ok(*i),
+            v => Err(ArgumentError::IvalidType {
+                expected: Type::Int,
+                found: gerning::Value::get_type(v),
+            }),
+        }
+    }
+}
+
+impl gerning::Typed<Value> for i64 {
+    fn get_type() -> Type {
+        Type::Int
+    }
+}
+
+fn add(_ctx: &mut (), a: i64, b: i64) -> i64 {
+    a + b
+}
+
+fn slow_double(_ctx: &mut (), a: i64) -> Result<i64, Infallible> {
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    Ok(a * 2)
+}
+
+fn increment(ctx: &mut i64) -> i64 {
+    *ctx += 1;
+    *ctx
+}
+
+fn main() -> Result<(), Error<Value>> {
+    let add = add.callable::<Value>().into_async::<Thread>();
+    let slow_double = slow_double.callable::<Value>().into_async::<Thread>();
+
+    let ret =
+        futures::executor::block_on(add.call_async(&mut Some(()), (1i64, 2i64).to_arguments()))?;
+    assert_eq!(ret, Value::Int(3));
+    println!("thread: {:?}", ret);
+
+    let ret = futures::executor::block_on(
+        slow_double.call_async(&mut Some(()), (21i64,).to_arguments()),
+    )?;
+    assert_eq!(ret, Value::Int(42));
+    println!("thread: {:?}", ret);
+
+    // Context mutations made on the executor are handed back to the caller
+    let increment_async = increment.callable::<Value>().into_async::<Thread>();
+    let mut counter = Some(0i64);
+    futures::executor::block_on(increment_async.call_async(&mut counter, ().to_arguments()))?;
+    futures::executor::block_on(increment_async.call_async(&mut counter, ().to_arguments()))?;
+    assert_eq!(counter, Some(2));
+    println!("counter: {:?}", counter);
+
+    // Functions without a context work with any context type
+    let mul = no_context(|a: i64, b: i64| a * b).callable::<Value>();
+    assert_eq!(
+        mul.call(&mut counter, (6i64, 7i64).to_arguments())?,
+        Value::Int(42)
+    );
+    let mul = no_context(|a: i64, b: i64| a * b)
+        .callable::<Value>()
+        .into_async::<Thread>();
+    let ret =
+        futures::executor::block_on(mul.call_async(&mut Some(()), (2i64, 3i64).to_arguments()))?;
+    assert_eq!(ret, Value::Int(6));
+    println!("no context: {:?}", ret);
+
+    let increment_shared = increment.callable::<Value>().into_async_shared::<Thread>();
+    let mut shared = std::sync::Arc::new(std::sync::Mutex::new(0i64));
+    futures::executor::block_on(increment_shared.call_async(&mut shared, ().to_arguments()))?;
+    assert_eq!(*shared.lock().unwrap(), 1);
+    println!("shared counter: {}", shared.lock().unwrap());
+
+    #[cfg(feature = "tokio")]
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime");
+        let callable = add_callable().into_async::<gerning::Tokio>();
+        let ret =
+            runtime.block_on(callable.call_async(&mut Some(()), (2i64, 3i64).to_arguments()))?;
+        assert_eq!(ret, Value::Int(5));
+        println!("tokio: {:?}", ret);
+    }
+
+    #[cfg(feature = "smol")]
+    {
+        let callable = add_callable().into_async::<gerning::Smol>();
+        let ret = smol::block_on(callable.call_async(&mut Some(()), (3i64, 4i64).to_arguments()))?;
+        assert_eq!(ret, Value::Int(7));
+        println!("smol: {:?}", ret);
+    }
+
+    #[cfg(feature = "async-std")]
+    {
+        let callable = add_callable().into_async::<gerning::AsyncStd>();
+        let ret = async_std::task::block_on(
+            callable.call_async(&mut Some(()), (4i64, 5i64).to_arguments()),
+        )?;
+        assert_eq!(ret, Value::Int(9));
+        println!("async-std: {:?}", ret);
+    }
+
+    #[cfg(feature = "blocking")]
+    {
+        let callable = add_callable().into_async::<gerning::Blocking>();
+        let ret = futures::executor::block_on(
+            callable.call_async(&mut Some(()), (5i64, 6i64).to_arguments()),
+        )?;
+        assert_eq!(ret, Value::Int(11));
+        println!("blocking: {:?}", ret);
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn add_callable() -> impl gerning::Callable<(), Value> + Clone + Send + 'static {
+    (add as fn(&mut (), i64, i64) -> i64).callable::<Value>()
+}
@@ -3,22 +3,42 @@ use crate::traits::Value;
 use super::error::ArgumentError;
 use alloc::vec::Vec;
 
+/// Arguments kept inline, before `Arguments` spills to the heap
+#[cfg(feature = "smallvec")]
+pub const INLINE_ARGUMENTS: usize = 3;
+
+#[cfg(feature = "smallvec")]
+type Storage<V> = smallvec::SmallVec<[V; INLINE_ARGUMENTS]>;
+
+#[cfg(not(feature = "smallvec"))]
+type Storage<V> = Vec<V>;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Arguments<V> {
-    args: Vec<V>,
+    args: Storage<V>,
 }
 
 impl<V> Default for Arguments<V> {
     fn default() -> Self {
         Arguments {
-            args: Vec::default(),
+            args: Storage::new(),
         }
     }
 }
 
 impl<V> Arguments<V> {
+    // Without the `smallvec` feature `Storage` is a `Vec`, and the conversion is a no-op
+    #[allow(clippy::useless_conversion)]
     pub fn new(args: Vec<V>) -> Arguments<V> {
-        Arguments { args }
+        Arguments { args: args.into() }
+    }
+
+    /// Arguments from a fixed number of values.
+    /// With the `smallvec` feature, up to `INLINE_ARGUMENTS` values do not allocate
+    pub fn from_array<const N: usize>(args: [V; N]) -> Arguments<V> {
+        Arguments {
+            args: args.into_iter().collect(),
+        }
     }
 }
 
@@ -40,7 +60,9 @@ impl<T: Value> Arguments<T> {
         V::try_from(val).map_err(|err| err.into())
     }
 
-    pub fn try_take<'a, V: TryFrom<T>>(&'a mut self, idx: usize) -> Result<V, ArgumentError<T>>
+    /// Remove the argument at `idx`, shifting the ones after it down.
+    /// `Owned` parameters move their argument out without shifting
+    pub fn try_take<V: TryFrom<T>>(&mut self, idx: usize) -> Result<V, ArgumentError<T>>
     where
         V::Error: Into<ArgumentError<T>>,
     {
@@ -76,6 +98,14 @@ impl<T: Value> Arguments<T> {
         self.args.get_mut(idx)
     }
 
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.args.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.args.iter_mut()
+    }
+
     pub fn len(&self) -> usize {
         self.args.len()
     }
@@ -89,8 +119,14 @@ impl<T: Value> Arguments<T> {
     }
 }
 
+#[cfg(feature = "smallvec")]
+pub type IntoIter<T> = smallvec::IntoIter<[T; INLINE_ARGUMENTS]>;
+
+#[cfg(not(feature = "smallvec"))]
+pub type IntoIter<T> = alloc::vec::IntoIter<T>;
+
 impl<T> IntoIterator for Arguments<T> {
-    type IntoIter = alloc::vec::IntoIter<T>;
+    type IntoIter = IntoIter<T>;
     type Item = T;
     fn into_iter(self) -> Self::IntoIter {
         self.args.into_iter()
@@ -99,13 +135,13 @@ impl<T> IntoIterator for Arguments<T> {
 
 #[derive(Debug)]
 pub struct ArgumentsBuilder<V> {
-    args: Vec<V>,
+    args: Storage<V>,
 }
 
 impl<V> Default for ArgumentsBuilder<V> {
     fn default() -> Self {
         ArgumentsBuilder {
-            args: Vec::default(),
+            args: Storage::new(),
         }
     }
 }
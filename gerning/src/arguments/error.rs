@@ -1,3 +1,4 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
 use core::{convert::Infallible, fmt};
 
 use crate::traits::Value;
@@ -5,9 +6,94 @@ use crate::traits::Value;
 #[derive(Debug)]
 pub enum ArgumentError<T: Value> {
     Infallible,
-    IvalidType { expected: T::Type, found: T::Type },
-    Missing { index: usize, arity: usize },
+    IvalidType {
+        expected: T::Type,
+        found: T::Type,
+    },
+    Missing {
+        index: usize,
+        arity: usize,
+    },
     IndexOutOfBounds(usize),
+    /// More arguments than parameters
+    TooMany {
+        expected: usize,
+        found: usize,
+    },
+    /// The argument for the parameter at `index` could not be extracted
+    Parameter {
+        index: usize,
+        name: Option<String>,
+        error: Box<ArgumentError<T>>,
+    },
+    /// Every parameter that failed, when extraction collects errors
+    Multiple(Vec<ArgumentError<T>>),
+}
+
+impl<T: Value> ArgumentError<T> {
+    /// Combine collected errors. A single error is returned as is
+    pub fn multiple(mut errors: Vec<ArgumentError<T>>) -> ArgumentError<T> {
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            ArgumentError::Multiple(errors)
+        }
+    }
+
+    /// Attach the position of the failing parameter
+    pub fn parameter(self, index: usize) -> ArgumentError<T> {
+        match self {
+            err @ (ArgumentError::Parameter { .. } | ArgumentError::Multiple(_)) => err,
+            err => ArgumentError::Parameter {
+                index,
+                name: None,
+                error: Box::new(err),
+            },
+        }
+    }
+
+    /// Name the failing parameter, if it is not named already
+    pub fn with_name(self, name: impl Into<String>) -> ArgumentError<T> {
+        match self {
+            ArgumentError::Parameter {
+                index,
+                name: None,
+                error,
+            } => ArgumentError::Parameter {
+                index,
+                name: Some(name.into()),
+                error,
+            },
+            err => err,
+        }
+    }
+
+    /// Name failing parameters by their position in `names`
+    pub fn with_names<S: AsRef<str>>(self, names: &[S]) -> ArgumentError<T> {
+        match self {
+            ArgumentError::Multiple(errors) => ArgumentError::Multiple(
+                errors
+                    .into_iter()
+                    .map(|err| err.with_names(names))
+                    .collect(),
+            ),
+            err => match err.index().and_then(|idx| names.get(idx)) {
+                Some(name) => err.with_name(name.as_ref()),
+                None => err,
+            },
+        }
+    }
+
+    /// The position of the failing parameter, if known
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            ArgumentError::Parameter { index, .. } | ArgumentError::Missing { index, .. } => {
+                Some(*index)
+            }
+            ArgumentError::IndexOutOfBounds(index) => Some(*index),
+            _ => None,
+        }
+    }
 }
 
 impl<T: Value> fmt::Display for ArgumentError<T>
@@ -28,10 +114,36 @@ where
             ArgumentError::IndexOutOfBounds(idx) => {
                 write!(f, "index out of bounds: {idx}")
             }
+            ArgumentError::TooMany { expected, found } => {
+                write!(
+                    f,
+                    "too many arguments. Expected: {expected}, found: {found}"
+                )
+            }
+            ArgumentError::Parameter {
+                index,
+                name: Some(name),
+                error,
+            } => {
+                write!(f, "argument {index} (`{name}`): {error}")
+            }
+            ArgumentError::Parameter { index, error, .. } => {
+                write!(f, "argument {index}: {error}")
+            }
+            ArgumentError::Multiple(errors) => {
+                write!(f, "{} invalid arguments", errors.len())?;
+                for (idx, error) in errors.iter().enumerate() {
+                    let sep = if idx == 0 { ": " } else { "; " };
+                    write!(f, "{sep}{error}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+impl<T: Value> core::error::Error for ArgumentError<T> {}
+
 impl<T: Value> From<Infallible> for ArgumentError<T> {
     fn from(_: Infallible) -> Self {
         ArgumentError::Infallible
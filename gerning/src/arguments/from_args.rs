@@ -3,91 +3,416 @@ use crate::{
     signature::Parameters,
     traits::{Typed, Value},
 };
-use core::convert::Infallible;
+use alloc::vec::Vec;
 
-pub trait FromArguments<'a, T: Value>: Sized + Send {
+/// How arguments are extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// Keep going after a parameter fails, and report every failure in one
+    /// `ArgumentError::Multiple`
+    pub collect: bool,
+    /// Reject arguments beyond the last parameter with `ArgumentError::TooMany`.
+    /// Off by default, so surplus arguments are ignored
+    pub strict: bool,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions::new()
+    }
+}
+
+impl ExtractOptions {
+    pub const fn new() -> ExtractOptions {
+        ExtractOptions {
+            collect: false,
+            strict: false,
+        }
+    }
+
+    pub const fn collect(mut self, collect: bool) -> Self {
+        self.collect = collect;
+        self
+    }
+
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
+
+/// Extracts function parameters from arguments.
+///
+/// `Self` describes the parameters, `Output` is what gets passed to the function.
+/// They only differ for parameters borrowing from the arguments: `(&str,)` extracts
+/// a `(&'a str,)` for every `'a`, so callables can require `for<'a> A: FromArguments<'a, V, M>`.
+///
+/// `M` is a marker, only there to tell the tuple implementations apart,
+/// see `FromArgument`
+pub trait FromArguments<'a, T: Value, M = ()>: Sized {
+    type Output: Send;
     type Error: Into<ArgumentError<T>>;
-    fn from_arguments(args: &'a mut Arguments<T>) -> Result<Self, Self::Error>;
+    fn from_arguments(args: &'a mut Arguments<T>) -> Result<Self::Output, Self::Error>;
 
+    fn from_arguments_with(
+        args: &'a mut Arguments<T>,
+        options: ExtractOptions,
+    ) -> Result<Self::Output, Self::Error> {
+        let _ = options;
+        Self::from_arguments(args)
+    }
+
+    /// Built on every call. Tuples can't keep theirs in a static, since a static
+    /// can't depend on the generic `T`; `DynService` takes signatures once at registration instead
     fn parameters() -> Parameters<T>;
 }
 
-impl<'a, T: Value> FromArguments<'a, T> for () {
-    type Error = Infallible;
-    fn from_arguments(_args: &'a mut Arguments<T>) -> Result<Self, Self::Error> {
-        Ok(())
+/// Marker for parameters converted from a reference to their argument
+pub enum ByRef {}
+
+/// Marker for parameters moved out of their argument
+pub enum ByValue {}
+
+/// Marker for parameters borrowing their argument
+pub enum Borrowed {}
+
+/// A single parameter, extracted from the slot of its argument.
+///
+/// Types converting from `&V` are extracted by reference. Wrap a parameter in `Owned`
+/// to move the argument into it instead, which saves a clone of strings and lists.
+/// References to a `BorrowArgument`, like `&str`, borrow the argument and save the clone altogether
+pub trait FromArgument<'a, V: Value, M>: Sized {
+    type Output: Send;
+
+    fn from_argument(value: &'a mut V) -> Result<Self::Output, ArgumentError<V>>;
+
+    fn parameter_type() -> V::Type;
+}
+
+impl<'a, V, T> FromArgument<'a, V, ByRef> for T
+where
+    V: Value + 'a,
+    T: TryFrom<&'a V> + Typed<V> + Send,
+    T::Error: Into<ArgumentError<V>>,
+{
+    type Output = T;
+
+    fn from_argument(value: &'a mut V) -> Result<Self::Output, ArgumentError<V>> {
+        let value: &'a V = value;
+        T::try_from(value).map_err(Into::into)
     }
 
-    fn parameters() -> Parameters<T> {
-        Parameters::default()
+    fn parameter_type() -> V::Type {
+        T::get_type()
     }
 }
 
-macro_rules! count {
-    (@step $idx: expr, $args:expr, $type1:ident, $( $type:ident ),*) => {
+/// A parameter taking its argument by value.
+/// The argument is replaced with `V::default()`, so later parameters keep their positions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Owned<T>(pub T);
 
-        let $type1 = $args.try_get_ref::<$type1>($idx)?;
-        count!(@step $idx + 1usize, $args, $($type),*);
-    };
+impl<T> Owned<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> core::ops::Deref for Owned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> core::ops::DerefMut for Owned<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, V, T> FromArgument<'a, V, ByValue> for Owned<T>
+where
+    V: Value + Default + 'a,
+    T: TryFrom<V> + Typed<V> + Send,
+    T::Error: Into<ArgumentError<V>>,
+{
+    type Output = Owned<T>;
+
+    fn from_argument(value: &'a mut V) -> Result<Self::Output, ArgumentError<V>> {
+        T::try_from(core::mem::take(value))
+            .map(Owned)
+            .map_err(Into::into)
+    }
+
+    fn parameter_type() -> V::Type {
+        T::get_type()
+    }
+}
+
+/// Types a parameter can borrow from its argument, like `str` or `[u8]`.
+///
+/// Implement it instead of `TryFrom<&'a V> for &'a str`; a parameter of type `&T`
+/// then lives as long as the call, without allocating a copy of the argument
+pub trait BorrowArgument<V: Value> {
+    fn borrow_argument(value: &V) -> Result<&Self, ArgumentError<V>>;
+
+    fn parameter_type() -> V::Type;
+}
+
+impl<'a, V, T> FromArgument<'a, V, Borrowed> for &T
+where
+    V: Value + 'a,
+    T: BorrowArgument<V> + Sync + ?Sized + 'a,
+{
+    type Output = &'a T;
+
+    fn from_argument(value: &'a mut V) -> Result<Self::Output, ArgumentError<V>> {
+        T::borrow_argument(value)
+    }
+
+    fn parameter_type() -> V::Type {
+        T::parameter_type()
+    }
+}
 
-    (@step $idx: expr, $args:expr, $type1:ident) => {
-        let $type1 = $args.try_get_ref::<$type1>($idx)?;
+fn extract<'a, V, T, M>(
+    slot: Option<&'a mut V>,
+    index: usize,
+    arity: usize,
+) -> Result<T::Output, ArgumentError<V>>
+where
+    V: Value,
+    T: FromArgument<'a, V, M>,
+{
+    let ret = match slot {
+        Some(value) => T::from_argument(value),
+        None => Err(ArgumentError::Missing { index, arity }),
     };
+    ret.map_err(|err| err.parameter(index))
+}
+
+impl<'a, T: Value> FromArguments<'a, T> for () {
+    type Output = ();
+    type Error = ArgumentError<T>;
+    fn from_arguments(args: &'a mut Arguments<T>) -> Result<Self::Output, Self::Error> {
+        Self::from_arguments_with(args, ExtractOptions::default())
+    }
 
-    (@step $_idx:expr, $args: expr,) => {};
+    fn from_arguments_with(
+        args: &'a mut Arguments<T>,
+        options: ExtractOptions,
+    ) -> Result<Self::Output, Self::Error> {
+        if options.strict && !args.is_empty() {
+            return Err(ArgumentError::TooMany {
+                expected: 0,
+                found: args.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn parameters() -> Parameters<T> {
+        Parameters::build().build()
+    }
+}
+
+macro_rules! one {
+    ($_ty: ident) => {
+        1usize
+    };
 }
 
 macro_rules! arguments {
-    ($first: ident) => {
-        impl<'a,V: Value + 'a, $first: Typed<V> + TryFrom<&'a V> + Send> FromArguments<'a, V> for ($first,)
-        where
-            $first::Error: Into<ArgumentError<V>>
-        {
+    (@impl $($ty: ident $marker: ident),*) => {
+        impl<'a, V: Value + 'a, $($ty: FromArgument<'a, V, $marker>, $marker),*> FromArguments<'a, V, ($($marker,)*)> for ($($ty,)*) {
+            type Output = ($(<$ty as FromArgument<'a, V, $marker>>::Output,)*);
             type Error = ArgumentError<V>;
-            fn from_arguments(args: &'a mut Arguments<V>) -> Result<Self, Self::Error> {
-                Ok((args.try_get_ref::<$first>(0)?,))
-            }
 
-            fn parameters() -> Parameters<V> {
-                Parameters::build().with($first::get_type()).build()
+            fn from_arguments(args: &'a mut Arguments<V>) -> Result<Self::Output, Self::Error> {
+                Self::from_arguments_with(args, ExtractOptions::default())
             }
-        }
-    };
 
-    ($first: ident $($rest: ident)*) => {
+            #[allow(non_snake_case)]
+            fn from_arguments_with(
+                args: &'a mut Arguments<V>,
+                options: ExtractOptions,
+            ) -> Result<Self::Output, Self::Error> {
+                const ARITY: usize = 0usize $(+ one!($ty))*;
 
-        arguments!($($rest)*);
+                let found = args.len();
+                let too_many = if options.strict && found > ARITY {
+                    Some(ArgumentError::TooMany {
+                        expected: ARITY,
+                        found,
+                    })
+                } else {
+                    None
+                };
 
+                // Every parameter gets its own slot, so by-reference and by-value
+                // parameters can be extracted side by side
+                let mut slots = args.iter_mut();
+                let mut index = 0usize;
 
-        impl<'a, V: Value + 'a, $first: Typed<V> + TryFrom<&'a V>  + Send, $($rest: Typed<V> + TryFrom<&'a V>  + Send),*> FromArguments<'a, V> for ($first,$($rest),*)
-        where
-            $first::Error: Into<ArgumentError<V>>,
-            $(
-                $rest::Error: Into<ArgumentError<V>>,
-            )*
-        {
-            type Error = ArgumentError<V>;
-            #[allow(non_snake_case)]
-            fn from_arguments(args: &'a mut Arguments<V>) -> Result<Self, Self::Error> {
+                if !options.collect {
+                    if let Some(err) = too_many {
+                        return Err(err);
+                    }
 
-                count!(@step 0, args, $first, $($rest),*);
+                    $(
+                        let $ty = extract::<V, $ty, $marker>(slots.next(), index, found)?;
+                        index += 1;
+                    )*
+                    let _ = index;
 
-                Ok((
-                    $first, $($rest),*
-                ))
+                    return Ok(($($ty,)*));
+                }
+
+                let mut errors = Vec::new();
+                $(
+                    let $ty = match extract::<V, $ty, $marker>(slots.next(), index, found) {
+                        Ok(ret) => Some(ret),
+                        Err(err) => {
+                            errors.push(err);
+                            None
+                        }
+                    };
+                    index += 1;
+                )*
+                let _ = index;
+                errors.extend(too_many);
+
+                if !errors.is_empty() {
+                    return Err(ArgumentError::multiple(errors));
+                }
+
+                Ok(($($ty.expect("extracted"),)*))
             }
 
             fn parameters() -> Parameters<V> {
                let mut params = Parameters::build();
-               params.add($first::get_type());
                $(
-                params.add($rest::get_type());
+                params.add(<$ty as FromArgument<'a, V, $marker>>::parameter_type());
                )*
 
                params.build()
             }
         }
     };
+
+    ($first: ident $first_marker: ident) => {
+        arguments!(@impl $first $first_marker);
+    };
+
+    ($first: ident $first_marker: ident $($rest: ident $rest_marker: ident)*) => {
+        arguments!($($rest $rest_marker)*);
+        arguments!(@impl $first $first_marker $(, $rest $rest_marker)*);
+    };
 }
 
-arguments!(T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15 T16);
+arguments!(
+    T1 M1 T2 M2 T3 M3 T4 M4 T5 M5 T6 M6 T7 M7 T8 M8 T9 M9 T10 M10 T11 M11 T12 M12 T13 M13 T14 M14
+    T15 M15 T16 M16
+);
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, sync::Arc, vec};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::testing::Value;
+
+    /// Counts how often it is cloned
+    #[derive(Debug, Default)]
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Tracked(self.0.clone())
+        }
+    }
+
+    impl crate::Value for Tracked {
+        type Type = ();
+
+        fn get_type(&self) {}
+    }
+
+    impl Typed<Tracked> for Tracked {
+        fn get_type() {}
+    }
+
+    fn args() -> Arguments<Value> {
+        Arguments::new(vec![Value::Int(1), Value::Int(2)])
+    }
+
+    #[test]
+    fn surplus_arguments_are_ignored_by_default() {
+        assert_eq!(<(i64,)>::from_arguments(&mut args()).unwrap(), (1,));
+        assert!(<()>::from_arguments(&mut args()).is_ok());
+    }
+
+    #[test]
+    fn strict_extraction_rejects_surplus_arguments() {
+        let strict = ExtractOptions::new().strict(true);
+        assert!(matches!(
+            <(i64,)>::from_arguments_with(&mut args(), strict),
+            Err(ArgumentError::TooMany {
+                expected: 1,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            <()>::from_arguments_with(&mut args(), strict),
+            Err(ArgumentError::TooMany {
+                expected: 0,
+                found: 2
+            })
+        ));
+        assert_eq!(
+            <(i64, i64)>::from_arguments_with(&mut args(), strict).unwrap(),
+            (1, 2)
+        );
+    }
+
+    #[test]
+    fn collected_errors_include_surplus_arguments() {
+        let options = ExtractOptions::new().strict(true).collect(true);
+        let mut args = Arguments::new(vec![Value::from("one"), Value::Int(2), Value::Int(3)]);
+        let Err(ArgumentError::Multiple(errors)) =
+            <(i64, String)>::from_arguments_with(&mut args, options)
+        else {
+            panic!("expected every error");
+        };
+        assert!(matches!(
+            errors[..],
+            [
+                ArgumentError::Parameter { index: 0, .. },
+                ArgumentError::Parameter { index: 1, .. },
+                ArgumentError::TooMany {
+                    expected: 2,
+                    found: 3
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn owned_parameters_move_their_argument() {
+        let clones = Arc::new(AtomicUsize::new(0));
+        let mut args = Arguments::new(vec![Tracked(clones.clone()), Tracked(clones.clone())]);
+
+        let (Owned(first), Owned(second)) =
+            <(Owned<Tracked>, Owned<Tracked>)>::from_arguments(&mut args).unwrap();
+        assert_eq!(clones.load(Ordering::SeqCst), 0);
+        assert!(Arc::ptr_eq(&first.0, &clones) && Arc::ptr_eq(&second.0, &clones));
+
+        // The taken arguments are left as defaults, in place
+        assert_eq!(args.len(), 2);
+        assert!(args.iter().all(|arg| !Arc::ptr_eq(&arg.0, &clones)));
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Arguments, ArgumentsBuilder};
+use super::Arguments;
 
 pub trait ToArguments<V> {
     fn to_arguments(self) -> Arguments<V>;
@@ -21,7 +21,7 @@ macro_rules! toargs {
         impl<V, $first: Into<V>> ToArguments<V> for ($first,)
         {
             fn to_arguments(self) -> Arguments<V> {
-                ArgumentsBuilder::default().with(self.0).build()
+                Arguments::from_array([self.0.into()])
             }
         }
     };
@@ -32,18 +32,8 @@ macro_rules! toargs {
         {
             #[allow(non_snake_case)]
             fn to_arguments(self) -> Arguments<V> {
-                let mut args = ArgumentsBuilder::default();
-
                 let ($first, $($rest),*) = self;
-
-                args.add($first);
-
-                $(
-                    args.add($rest);
-                )*
-
-                args.build()
-
+                Arguments::from_array([$first.into(), $($rest.into()),*])
             }
         }
     }
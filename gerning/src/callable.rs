@@ -1,13 +1,18 @@
 #[cfg(feature = "async")]
-use crate::AsyncCallable;
+use crate::{executor::Executor, AsyncCallable};
 use crate::{
     arguments::Arguments,
     error::Error,
+    named::Named,
+    retry::{Retry, RetryPolicy},
+    shared::Locked,
     signature::{Parameters, Signature},
     traits::{Typed, Value},
 };
 use alloc::boxed::Box;
 #[cfg(feature = "async")]
+use alloc::sync::Arc;
+#[cfg(feature = "async")]
 use core::{marker::PhantomData, pin::Pin};
 #[cfg(feature = "async")]
 use futures_core::Future;
@@ -80,51 +85,26 @@ where
 //     }
 // }
 
-#[cfg(feature = "async")]
-pub trait Executor {
-    type Error;
-    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
-        func: F,
-    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>>;
-}
-
-#[cfg(feature = "tokio")]
-pub struct Tokio;
-
-#[cfg(feature = "tokio")]
-impl Executor for Tokio {
-    type Error = tokio::task::JoinError;
-    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
-        func: F,
-    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
-        Box::pin(tokio::task::spawn_blocking(func))
-    }
-}
-
-#[cfg(feature = "smol")]
-pub struct Smol;
-
-#[cfg(feature = "smol")]
-impl Executor for Smol {
-    type Error = ();
-    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
-        func: F,
-    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
-        Box::pin(async move { Ok(smol::unblock(func).await) })
-    }
-}
-
 pub trait CallableExt<C, V: Value>: Callable<C, V> {
+    /// Run the callable on a blocking executor.
+    /// The context is moved to the executor and handed back when the call completes, see `Move`
     #[cfg(feature = "async")]
     fn into_async<E>(self) -> IntoAsync<Self, C, E, V>
     where
         Self: Sized,
         E: Executor,
     {
-        IntoAsync {
-            callable: self,
-            _executor: PhantomData,
-        }
+        IntoAsync::new(self)
+    }
+
+    /// Run the callable on a blocking executor against a context shared through `Arc<Mutex<C>>`
+    #[cfg(all(feature = "async", feature = "std"))]
+    fn into_async_shared<E>(self) -> IntoAsync<Self, C, E, V, Shared>
+    where
+        Self: Sized,
+        E: Executor,
+    {
+        IntoAsync::new(self)
     }
 
     fn boxed(self) -> Box<dyn Callable<C, V>>
@@ -133,38 +113,196 @@ pub trait CallableExt<C, V: Value>: Callable<C, V> {
     {
         Box::new(self)
     }
+
+    fn retry(self, policy: RetryPolicy<V>) -> Retry<Self, V>
+    where
+        Self: Sized,
+    {
+        Retry::new(self, policy)
+    }
+
+    /// Serve the callable from a context shared behind a `Mutex`
+    fn locked(self) -> Locked<Self>
+    where
+        Self: Sized,
+    {
+        Locked::new(self)
+    }
+
+    /// Name the callable in the errors it returns
+    fn named(self, name: impl Into<alloc::string::String>) -> Named<Self>
+    where
+        Self: Sized,
+    {
+        Named::new(self, name)
+    }
 }
 
 impl<C, T, V: Value> CallableExt<T, V> for C where C: Callable<T, V> {}
 
+/// How a context is handed to a callable running on an executor
+#[cfg(feature = "async")]
+pub trait ContextMode<T> {
+    /// The context type of the async callable
+    type Context;
+    /// What is sent to the executor
+    type Handle: Send + 'static;
+
+    fn lend(ctx: &mut Self::Context) -> Self::Handle;
+
+    fn with<V: Value>(
+        handle: &mut Self::Handle,
+        func: impl FnOnce(&mut T) -> Result<V, Error<V>>,
+    ) -> Result<V, Error<V>>;
+
+    fn restore(ctx: &mut Self::Context, handle: Self::Handle);
+}
+
+/// Moves the context to the executor and back again when the call completes.
+///
+/// The context is taken out of an `Option`, so it is `None` while a call is in flight.
+/// It stays `None` if the future is dropped before the call completes, or if the executor
+/// fails to run the call, and later calls fail with `ContextMissing` until it is put back.
+/// With the `std` feature a panicking callable hands the context back, possibly half updated,
+/// and the call fails with `ThreadPanic`
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, Copy)]
+pub struct Move;
+
+#[cfg(feature = "async")]
+impl<T: Send + 'static> ContextMode<T> for Move {
+    type Context = Option<T>;
+    type Handle = Option<T>;
+
+    fn lend(ctx: &mut Option<T>) -> Option<T> {
+        ctx.take()
+    }
+
+    fn with<V: Value>(
+        handle: &mut Option<T>,
+        func: impl FnOnce(&mut T) -> Result<V, Error<V>>,
+    ) -> Result<V, Error<V>> {
+        match handle {
+            Some(ctx) => func(ctx),
+            None => Err(Error::new(ContextMissing)),
+        }
+    }
+
+    fn restore(ctx: &mut Option<T>, handle: Option<T>) {
+        *ctx = handle;
+    }
+}
+
+/// A `Move` callable was called without a context,
+/// most likely because an earlier call never handed it back
 #[cfg(feature = "async")]
-pub struct IntoAsync<C, T, E, V> {
-    callable: C,
-    _executor: PhantomData<(T, E, V)>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextMissing;
+
+#[cfg(feature = "async")]
+impl core::fmt::Display for ContextMissing {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "context missing")
+    }
 }
 
 #[cfg(feature = "async")]
-impl<C, T, E, V> AsyncCallable<T, V> for IntoAsync<C, T, E, V>
+impl core::error::Error for ContextMissing {}
+
+/// Shares the context with the executor through an `Arc<Mutex<T>>`,
+/// which is locked for the duration of the call
+#[cfg(all(feature = "async", feature = "std"))]
+#[derive(Debug, Clone, Copy)]
+pub struct Shared;
+
+#[cfg(all(feature = "async", feature = "std"))]
+impl<T: Send + 'static> ContextMode<T> for Shared {
+    type Context = Arc<std::sync::Mutex<T>>;
+    type Handle = Arc<std::sync::Mutex<T>>;
+
+    fn lend(ctx: &mut Self::Context) -> Self::Handle {
+        ctx.clone()
+    }
+
+    fn with<V: Value>(
+        handle: &mut Self::Handle,
+        func: impl FnOnce(&mut T) -> Result<V, Error<V>>,
+    ) -> Result<V, Error<V>> {
+        let mut lock = handle.lock().unwrap_or_else(|err| err.into_inner());
+        func(&mut lock)
+    }
+
+    fn restore(_ctx: &mut Self::Context, _handle: Self::Handle) {}
+}
+
+#[cfg(feature = "async")]
+pub struct IntoAsync<C, T, E, V, M = Move> {
+    callable: Arc<C>,
+    _executor: PhantomData<(T, E, V, M)>,
+}
+
+#[cfg(feature = "async")]
+impl<C, T, E, V, M> IntoAsync<C, T, E, V, M> {
+    pub fn new(callable: C) -> IntoAsync<C, T, E, V, M> {
+        IntoAsync {
+            callable: Arc::new(callable),
+            _executor: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C, T, E, V, M> Clone for IntoAsync<C, T, E, V, M> {
+    fn clone(&self) -> Self {
+        IntoAsync {
+            callable: self.callable.clone(),
+            _executor: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C, T, E, V, M> AsyncCallable<M::Context, V> for IntoAsync<C, T, E, V, M>
 where
-    C: Callable<T, V> + Clone + Send + 'static,
+    C: Callable<T, V> + Send + Sync + 'static,
+    M: ContextMode<T> + 'static,
+    M::Context: Send,
     E: Executor + 'static,
-    E::Error: core::fmt::Debug + Send + Sync + 'static,
+    E::Error: core::error::Error + Send + Sync + 'static,
     V: 'static + Value + Send,
     V::Type: Send,
-    T: Send + Sync + 'static + Clone,
+    T: 'static,
 {
     type Future<'a> = Pin<Box<dyn Future<Output = Result<V, Error<V>>> + Send + 'a>>;
     fn signature(&self) -> Signature<V> {
         self.callable.signature()
     }
 
-    fn call_async(&self, ctx: &mut T, args: Arguments<V>) -> Self::Future<'_> {
+    fn call_async<'a>(&'a self, ctx: &'a mut M::Context, args: Arguments<V>) -> Self::Future<'a> {
         let callable = self.callable.clone();
-        let mut ctx = ctx.clone();
         Box::pin(async move {
-            E::spawn_blocking(move || callable.call(&mut ctx, args))
-                .await
-                .map_err(|err| Error::Runtime(Box::new(err)))?
+            let mut handle = M::lend(ctx);
+
+            let ret = E::spawn_blocking(move || {
+                let call = || M::with(&mut handle, |ctx| callable.call(ctx, args));
+                // Catching the panic here, rather than leaving it to the executor,
+                // is what gets the context back to the caller
+                #[cfg(feature = "std")]
+                let ret = std::panic::catch_unwind(std::panic::AssertUnwindSafe(call))
+                    .unwrap_or_else(|_| Err(Error::new(crate::ThreadPanic)));
+                #[cfg(not(feature = "std"))]
+                let ret = call();
+                (handle, ret)
+            })
+            .await;
+
+            match ret {
+                Ok((handle, ret)) => {
+                    M::restore(ctx, handle);
+                    ret
+                }
+                Err(err) => Err(Error::Runtime(Box::new(err))),
+            }
         })
     }
 }
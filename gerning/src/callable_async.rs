@@ -1,9 +1,15 @@
+use crate::cancel::{Cancellable, CancellationToken};
+use crate::named::Named;
+use crate::retry::{AsyncRetry, RetryPolicy};
+use crate::shared::Locked;
 use crate::signature::{Parameters, Signature};
+use crate::time::{Timeout, Timer};
 use crate::traits::{Typed, Value};
 use crate::{arguments::Arguments, Error, Resultable};
 use alloc::boxed::Box;
 use core::future::{Future, IntoFuture};
 use core::pin::Pin;
+use core::time::Duration;
 use futures_core::future::{BoxFuture, LocalBoxFuture};
 
 pub trait AsyncCallable<C, V: Value> {
@@ -35,6 +41,45 @@ pub trait AsyncCallableExt<C, V: Value>: AsyncCallable<C, V> {
     {
         Box::new(self)
     }
+
+    fn timeout<T>(self, duration: Duration) -> Timeout<Self, T>
+    where
+        Self: Sized,
+        T: Timer,
+    {
+        Timeout::new(self, duration)
+    }
+
+    fn with_cancellation(self, token: CancellationToken) -> Cancellable<Self>
+    where
+        Self: Sized,
+    {
+        Cancellable::new(self, token)
+    }
+
+    fn retry<T>(self, policy: RetryPolicy<V>) -> AsyncRetry<Self, T, V>
+    where
+        Self: Sized,
+        T: Timer,
+    {
+        AsyncRetry::new(self, policy)
+    }
+
+    /// Serve the callable from a context shared behind an async `Mutex`
+    fn locked(self) -> Locked<Self>
+    where
+        Self: Sized,
+    {
+        Locked::new(self)
+    }
+
+    /// Name the callable in the errors it returns
+    fn named(self, name: impl Into<alloc::string::String>) -> Named<Self>
+    where
+        Self: Sized,
+    {
+        Named::new(self, name)
+    }
 }
 
 impl<T, C, V: Value> AsyncCallableExt<C, V> for T where T: AsyncCallable<C, V> {}
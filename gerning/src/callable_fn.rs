@@ -1,71 +1,105 @@
 #[cfg(feature = "async")]
 use crate::callable_async::AsyncCallable;
+#[cfg(feature = "async")]
+use crate::callable_stream::{AsyncFuncStream, FuncStream, StreamCallable};
 use core::marker::PhantomData;
 #[cfg(feature = "async")]
-use core::pin::Pin;
+use core::{marker::PhantomPinned, pin::Pin};
 #[cfg(feature = "async")]
 use pin_project_lite::pin_project;
 
 use crate::{
-    arguments::{Arguments, FromArguments},
+    arguments::{Arguments, ExtractOptions, FromArguments},
     func::Func,
     signature::Signature,
     traits::{Typed, Value},
     Callable, Error, Resultable,
 };
 
-pub struct CallableFunc<F, C, A, V> {
-    func: F,
-    _args: PhantomData<(C, A, V)>,
+pub struct CallableFunc<F, C, A, V, M = ()> {
+    pub(crate) func: F,
+    pub(crate) options: ExtractOptions,
+    _args: PhantomData<(C, A, V, M)>,
 }
 
-impl<F: Clone, C, A, V> Clone for CallableFunc<F, C, A, V> {
+impl<F: Clone, C, A, V, M> Clone for CallableFunc<F, C, A, V, M> {
     fn clone(&self) -> Self {
         CallableFunc {
             func: self.func.clone(),
+            options: self.options,
             _args: PhantomData,
         }
     }
 }
 
-impl<F: Copy, C, A, V> Copy for CallableFunc<F, C, A, V> {}
+impl<F: Copy, C, A, V, M> Copy for CallableFunc<F, C, A, V, M> {}
 
-unsafe impl<F: Send, C, A, V> Send for CallableFunc<C, F, A, V> {}
+unsafe impl<F: Send, C, A, V, M> Send for CallableFunc<C, F, A, V, M> {}
 
-unsafe impl<F: Sync, C, A, V> Sync for CallableFunc<C, F, A, V> {}
+unsafe impl<F: Sync, C, A, V, M> Sync for CallableFunc<C, F, A, V, M> {}
 
-impl<F, C, A, V: Value> CallableFunc<F, C, A, V>
+impl<F, C, A, V: Value, M> CallableFunc<F, C, A, V, M>
 where
-    for<'a> A: FromArguments<'a, V>,
+    for<'a> A: FromArguments<'a, V, M>,
 {
     pub fn new(func: F) -> Self
     where
         F: crate::func::Func<C, A>,
     {
+        CallableFunc::from_func(func)
+    }
+
+    /// Without the `Func` bound, for wrappers calling the function another way
+    pub(crate) fn from_func(func: F) -> Self {
         CallableFunc {
             func,
+            options: ExtractOptions::default(),
             _args: PhantomData,
         }
     }
+
+    pub fn extract_options(mut self, options: ExtractOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Report every failing argument at once instead of only the first
+    pub fn collect_errors(mut self) -> Self {
+        self.options.collect = true;
+        self
+    }
+
+    /// Fail with `ArgumentError::TooMany` on arguments beyond the last parameter, instead of ignoring them
+    pub fn strict(mut self) -> Self {
+        self.options.strict = true;
+        self
+    }
+
+    /// Stream the items of the stream an async function resolves to
+    #[cfg(feature = "async")]
+    pub fn async_stream(self) -> AsyncStreamFunc<F, C, A, V, M> {
+        AsyncStreamFunc { callable: self }
+    }
 }
 
-impl<F, C, A, V: Value> Callable<C, V> for CallableFunc<F, C, A, V>
+impl<F, C, A, V: Value, M, U> Callable<C, V> for CallableFunc<F, C, A, V, M>
 where
-    for<'a> A: FromArguments<'a, V>,
-    F: crate::func::Func<C, A>,
-    F::Output: Resultable,
-    <F::Output as Resultable>::Ok: Into<V> + Typed<V>,
-    <F::Output as Resultable>::Error: Into<Error<V>>,
+    for<'a> A: FromArguments<'a, V, M>,
+    for<'a> F: crate::func::Func<C, <A as FromArguments<'a, V, M>>::Output, Output = U>,
+    U: Resultable,
+    U::Ok: Into<V> + Typed<V>,
+    U::Error: Into<Error<V>>,
 {
     fn signature(&self) -> Signature<V> {
         Signature::new(
-            A::parameters(),
-            <<F::Output as Resultable>::Ok as Typed<V>>::get_type(),
+            <A as FromArguments<'_, V, M>>::parameters(),
+            <U::Ok as Typed<V>>::get_type(),
         )
     }
 
-    fn call<'a>(&self, ctx: &'a mut C, mut args: Arguments<V>) -> Result<V, Error<V>> {
-        let args = A::from_arguments(&mut args).map_err(|err| err.into())?;
+    fn call(&self, ctx: &mut C, mut args: Arguments<V>) -> Result<V, Error<V>> {
+        let args = <A as FromArguments<'_, V, M>>::from_arguments_with(&mut args, self.options)
+            .map_err(|err| Error::Argument(err.into()))?;
 
         Ok(self
             .func
@@ -77,106 +111,298 @@ where
 }
 
 #[cfg(feature = "async")]
-impl<F, C, A, V: Value + 'static> AsyncCallable<C, V> for CallableFunc<F, C, A, V>
+impl<F, C, A, V: Value + 'static, M, U> AsyncCallable<C, V> for CallableFunc<F, C, A, V, M>
 where
-    for<'a> A: FromArguments<'a, V> + 'a,
-    // for<'a> C: 'a,
-    F: crate::func::AsyncFunc<C, A> + 'static,
-    // for<'a> F::Output<'a>: Future + 'a,
-    F::Output: Resultable,
-    <F::Output as Resultable>::Error: Into<Error<V>>,
-    <F::Output as Resultable>::Ok: Into<V> + Typed<V>,
+    for<'a> A: FromArguments<'a, V, M>,
+    for<'a> F: crate::func::AsyncFunc<C, <A as FromArguments<'a, V, M>>::Output, Output = U>,
+    F: 'static,
+    U: Resultable,
+    U::Error: Into<Error<V>>,
+    U::Ok: Into<V> + Typed<V>,
 {
-    type Future<'a> = CallableFuncFuture<'a, F::Future<'a>, V> where C: 'a;
+    type Future<'a> = CallableFuncFuture<
+        'a,
+        F,
+        &'a mut C,
+        <F as crate::func::AsyncFunc<C, <A as FromArguments<'a, V, M>>::Output>>::Future<'a>,
+        V,
+    >
+    where
+        C: 'a,
+        A: 'a,
+        M: 'a;
 
     fn signature(&self) -> Signature<V> {
         Signature::new(
-            A::parameters(),
-            <<F::Output as Resultable>::Ok as Typed<V>>::get_type(),
+            <A as FromArguments<'_, V, M>>::parameters(),
+            <U::Ok as Typed<V>>::get_type(),
         )
     }
 
-    fn call_async<'a>(&'a self, ctx: &'a mut C, mut args: Arguments<V>) -> Self::Future<'a> {
-        let state = match A::from_arguments(&mut args).map_err(|err| err.into()) {
-            Err(err) => CallableFuncFutureState::Error {
-                error: Some(err.into()),
+    fn call_async<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Future<'a> {
+        CallableFuncFuture::new(
+            &self.func,
+            ctx,
+            args,
+            self.options,
+            |func, ctx, args, options| {
+                let args = <A as FromArguments<'_, V, M>>::from_arguments_with(args, options)
+                    .map_err(|err| Error::Argument(err.into()))?;
+                Ok(func.call(ctx, args))
             },
-            Ok(args) => CallableFuncFutureState::Future {
-                future: self.func.call(ctx, args),
-            },
-        };
+        )
+    }
+}
 
-        CallableFuncFuture {
-            func: state,
-            lifetime: core::marker::PhantomData,
+// Streams outlive the call, so their parameters can't borrow the arguments
+#[cfg(feature = "async")]
+impl<F, C, A, V: Value, M> StreamCallable<C, V> for CallableFunc<F, C, A, V, M>
+where
+    for<'a> A: FromArguments<'a, V, M, Output = A>,
+    F: crate::func::Func<C, A>,
+    F::Output: futures_core::Stream,
+    <F::Output as futures_core::Stream>::Item: Resultable,
+    <<F::Output as futures_core::Stream>::Item as Resultable>::Ok: Into<V> + Typed<V>,
+    <<F::Output as futures_core::Stream>::Item as Resultable>::Error: Into<Error<V>>,
+{
+    type Stream<'a> = FuncStream<F::Output, V>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        Signature::stream(
+            <A as FromArguments<'_, V, M>>::parameters(),
+            <<<F::Output as futures_core::Stream>::Item as Resultable>::Ok as Typed<V>>::get_type(),
+        )
+    }
+
+    fn call_stream<'a>(&'a self, ctx: &'a mut C, mut args: Arguments<V>) -> Self::Stream<'a> {
+        let args = <A as FromArguments<'_, V, M>>::from_arguments_with(&mut args, self.options);
+        match args {
+            Ok(args) => FuncStream::new(self.func.call(ctx, args)),
+            Err(err) => FuncStream::error(Error::Argument(err.into())),
+        }
+    }
+}
+
+/// A `StreamCallable` for async functions resolving to a stream,
+/// see `CallableFunc::async_stream`
+#[cfg(feature = "async")]
+pub struct AsyncStreamFunc<F, C, A, V, M = ()> {
+    callable: CallableFunc<F, C, A, V, M>,
+}
+
+#[cfg(feature = "async")]
+impl<F: Clone, C, A, V, M> Clone for AsyncStreamFunc<F, C, A, V, M> {
+    fn clone(&self) -> Self {
+        AsyncStreamFunc {
+            callable: self.callable.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, C, A, V: Value, M, U> StreamCallable<C, V> for AsyncStreamFunc<F, C, A, V, M>
+where
+    for<'a> A: FromArguments<'a, V, M, Output = A>,
+    F: crate::func::AsyncFunc<C, A, Output = U>,
+    U: futures_core::Stream,
+    U::Item: Resultable,
+    <U::Item as Resultable>::Ok: Into<V> + Typed<V>,
+    <U::Item as Resultable>::Error: Into<Error<V>>,
+{
+    type Stream<'a> = AsyncFuncStream<F::Future<'a>, U, V>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        Signature::stream(
+            <A as FromArguments<'_, V, M>>::parameters(),
+            <<U::Item as Resultable>::Ok as Typed<V>>::get_type(),
+        )
+    }
+
+    fn call_stream<'a>(&'a self, ctx: &'a mut C, mut args: Arguments<V>) -> Self::Stream<'a> {
+        let options = self.callable.options;
+        match <A as FromArguments<'_, V, M>>::from_arguments_with(&mut args, options) {
+            Ok(args) => AsyncFuncStream::new(self.callable.func.call(ctx, args)),
+            Err(err) => AsyncFuncStream::error(Error::Argument(err.into())),
         }
     }
 }
 
+/// Extracts the parameters and starts the call, with the arguments borrowed from the future
+#[cfg(feature = "async")]
+pub(crate) type StartFn<'a, F, X, U, V> =
+    fn(&'a F, X, &'a mut Arguments<V>, ExtractOptions) -> Result<U, Error<V>>;
+
 #[cfg(feature = "async")]
 pin_project! {
     #[project = EnumProj]
-    enum CallableFuncFutureState<F, V: Value> {
-        Error {
-            error: Option<Error<V>>
+    enum CallableFuncFutureState<'a, F, X, U, V: Value> {
+        Init {
+            func: &'a F,
+            ctx: Option<X>,
+            options: ExtractOptions,
+            start: StartFn<'a, F, X, U, V>,
         },
         Future {
             #[pin]
-            future: F
-        }
+            future: U
+        },
+        Done,
     }
 }
 
 #[cfg(feature = "async")]
 pin_project! {
-    pub struct CallableFuncFuture<'a, F, V: Value> {
+    /// Owns the arguments of the call, so parameters can borrow from them.
+    /// Arguments are extracted on the first poll, once the future is pinned
+    pub struct CallableFuncFuture<'a, F, X, U, V: Value> {
         #[pin]
-        func: CallableFuncFutureState<F, V>,
-        lifetime: core::marker::PhantomData<&'a V>
+        state: CallableFuncFutureState<'a, F, X, U, V>,
+        // Dropped after the state, which may borrow from it
+        args: Arguments<V>,
+        #[pin]
+        _pin: PhantomPinned,
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, F, X, U, V: Value> CallableFuncFuture<'a, F, X, U, V> {
+    pub(crate) fn new(
+        func: &'a F,
+        ctx: X,
+        args: Arguments<V>,
+        options: ExtractOptions,
+        start: StartFn<'a, F, X, U, V>,
+    ) -> Self {
+        CallableFuncFuture {
+            state: CallableFuncFutureState::Init {
+                func,
+                ctx: Some(ctx),
+                options,
+                start,
+            },
+            args,
+            _pin: PhantomPinned,
+        }
     }
 }
 
 #[cfg(feature = "async")]
-unsafe impl<'a, F: Send, V: Value> Send for CallableFuncFuture<'a, F, V> {}
+unsafe impl<'a, F: Sync, X: Send, U: Send, V: Value + Send> Send
+    for CallableFuncFuture<'a, F, X, U, V>
+{
+}
 
 #[cfg(feature = "async")]
-impl<'a, F, V: Value> core::future::Future for CallableFuncFuture<'a, F, V>
+impl<'a, F, X, U, V: Value> core::future::Future for CallableFuncFuture<'a, F, X, U, V>
 where
-    F: core::future::Future + 'a,
-    F::Output: Resultable,
-    <F::Output as Resultable>::Error: Into<Error<V>>,
-    <F::Output as Resultable>::Ok: Into<V> + Typed<V>,
+    U: core::future::Future,
+    U::Output: Resultable,
+    <U::Output as Resultable>::Error: Into<Error<V>>,
+    <U::Output as Resultable>::Ok: Into<V> + Typed<V>,
 {
     type Output = Result<V, Error<V>>;
 
     fn poll(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut core::task::Context<'_>,
     ) -> core::task::Poll<Self::Output> {
         use core::task::Poll;
-        let this = self.as_mut().project();
-
-        match this.func.project() {
-            EnumProj::Error { error } => Poll::Ready(Err(error.take().expect("call after finish"))),
-            EnumProj::Future { future } => match future.poll(cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(ret) => match ret.into_result() {
-                    Ok(ret) => Poll::Ready(Ok(ret.into())),
-                    Err(err) => Poll::Ready(Err(err.into())),
-                },
-            },
+        let mut this = self.project();
+
+        loop {
+            match this.state.as_mut().project() {
+                EnumProj::Init {
+                    func,
+                    ctx,
+                    options,
+                    start,
+                } => {
+                    let (func, start, options) = (*func, *start, *options);
+                    let ctx = ctx.take().expect("ctx");
+                    // Safety: the future is pinned, so the arguments stay put,
+                    // and they are only dropped after the state borrowing them
+                    let args = unsafe { &mut *(&mut *this.args as *mut Arguments<V>) };
+
+                    match start(func, ctx, args, options) {
+                        Ok(future) => this.state.set(CallableFuncFutureState::Future { future }),
+                        Err(err) => {
+                            this.state.set(CallableFuncFutureState::Done);
+                            return Poll::Ready(Err(err));
+                        }
+                    }
+                }
+                EnumProj::Future { future } => {
+                    let ret = match future.poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(ret) => ret,
+                    };
+                    this.state.set(CallableFuncFutureState::Done);
+                    return Poll::Ready(match ret.into_result() {
+                        Ok(ret) => Ok(ret.into()),
+                        Err(err) => Err(err.into()),
+                    });
+                }
+                EnumProj::Done => panic!("poll after done"),
+            }
         }
     }
 }
 
-pub trait FuncExt<C, A>: Func<C, A> {
-    fn callable<V: Value>(self) -> CallableFunc<Self, C, A, V>
+pub trait FuncExt<C, A, M = ()>: Func<C, A> {
+    fn callable<V: Value>(self) -> CallableFunc<Self, C, A, V, M>
     where
         Self: Sized,
-        for<'a> A: FromArguments<'a, V>,
+        for<'a> A: FromArguments<'a, V, M>,
     {
         CallableFunc::new(self)
     }
 }
 
-impl<F, C, A> FuncExt<C, A> for F where F: Func<C, A> {}
+impl<F, C, A, M> FuncExt<C, A, M> for F where F: Func<C, A> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{arguments::ToArguments, testing::Value};
+
+    fn greet(_ctx: &mut (), greeting: &str, name: &str) -> String {
+        alloc::format!("{greeting}, {name}")
+    }
+
+    #[test]
+    fn parameters_borrow_their_arguments() {
+        let callable = greet.callable::<Value>();
+        let ret = callable.call(&mut (), ("Hello", "World").to_arguments());
+        assert_eq!(ret.unwrap(), Value::from("Hello, World"));
+
+        let err = callable
+            .call(&mut (), ("Hello", 1i64).to_arguments())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Argument(crate::arguments::ArgumentError::Parameter { index: 1, .. })
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_parameters_borrow_their_arguments() {
+        let len = |_ctx: &mut (), word: &str| {
+            let len = word.len() as i64;
+            async move { len }
+        };
+        let callable = len.callable::<Value>();
+
+        let mut ctx = ();
+        let future = callable.call_async(&mut ctx, ("four",).to_arguments());
+        assert_eq!(futures::executor::block_on(future).unwrap(), Value::Int(4));
+    }
+}
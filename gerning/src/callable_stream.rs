@@ -0,0 +1,322 @@
+use crate::signature::{Parameters, Signature};
+use crate::traits::{Typed, Value};
+use crate::{arguments::Arguments, Error, Resultable};
+use alloc::boxed::Box;
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_core::{
+    ready,
+    stream::{BoxStream, LocalBoxStream},
+    Future, Stream,
+};
+use pin_project_lite::pin_project;
+
+/// A callable producing many results over time
+pub trait StreamCallable<C, V: Value> {
+    type Stream<'a>: Stream<Item = Result<V, Error<V>>>
+    where
+        Self: 'a,
+        C: 'a;
+    fn signature(&self) -> Signature<V>;
+
+    fn call_stream<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Stream<'a>;
+}
+
+pub trait StreamCallableExt<C, V: Value>: StreamCallable<C, V> {
+    fn boxed(self) -> BoxStreamCallable<'static, C, V>
+    where
+        Self: Sized + 'static + Send + Sync,
+        for<'a> Self::Stream<'a>: Send,
+        V: 'static,
+        for<'a> C: 'a,
+    {
+        Box::new(self)
+    }
+
+    fn boxed_local(self) -> LocalBoxStreamCallable<'static, C, V>
+    where
+        Self: Sized + 'static + Send + Sync,
+        V: 'static,
+        C: 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<T, C, V: Value> StreamCallableExt<C, V> for T where T: StreamCallable<C, V> {}
+
+pub type BoxStreamCallable<'a, C, V> = Box<dyn internal::BoxStreamCall<C, V> + Send + Sync + 'a>;
+
+pub type LocalBoxStreamCallable<'a, C, V> =
+    Box<dyn internal::BoxLocalStreamCall<C, V> + Send + Sync + 'a>;
+
+mod internal {
+    use super::*;
+
+    pub trait BoxStreamCall<C, V: Value> {
+        fn signature(&self) -> Signature<V>;
+        fn call<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> BoxStream<'a, Result<V, Error<V>>>;
+    }
+
+    impl<T, C, V> BoxStreamCall<C, V> for T
+    where
+        T: StreamCallable<C, V>,
+        for<'a> T::Stream<'a>: Send,
+        V: Value + 'static,
+        C: 'static,
+    {
+        fn signature(&self) -> Signature<V> {
+            <T as StreamCallable<C, V>>::signature(self)
+        }
+
+        fn call<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> BoxStream<'a, Result<V, Error<V>>> {
+            Box::pin(<T as StreamCallable<C, V>>::call_stream(self, ctx, args))
+        }
+    }
+
+    pub trait BoxLocalStreamCall<C, V: Value> {
+        fn signature(&self) -> Signature<V>;
+        fn call<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> LocalBoxStream<'a, Result<V, Error<V>>>;
+    }
+
+    impl<T, C, V> BoxLocalStreamCall<C, V> for T
+    where
+        T: StreamCallable<C, V>,
+        V: Value + 'static,
+        C: 'static,
+    {
+        fn signature(&self) -> Signature<V> {
+            <T as StreamCallable<C, V>>::signature(self)
+        }
+
+        fn call<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> LocalBoxStream<'a, Result<V, Error<V>>> {
+            Box::pin(<T as StreamCallable<C, V>>::call_stream(self, ctx, args))
+        }
+    }
+}
+
+impl<C, V: Value + 'static> StreamCallable<C, V> for BoxStreamCallable<'static, C, V> {
+    type Stream<'a>
+        = BoxStream<'a, Result<V, Error<V>>>
+    where
+        C: 'a;
+    fn signature(&self) -> Signature<V> {
+        (**self).signature()
+    }
+    fn call_stream<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Stream<'a> {
+        (**self).call(ctx, args)
+    }
+}
+
+impl<C, V: Value + 'static> StreamCallable<C, V> for LocalBoxStreamCallable<'static, C, V> {
+    type Stream<'a>
+        = LocalBoxStream<'a, Result<V, Error<V>>>
+    where
+        C: 'a;
+    fn signature(&self) -> Signature<V> {
+        (**self).signature()
+    }
+    fn call_stream<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Stream<'a> {
+        (**self).call(ctx, args)
+    }
+}
+
+impl<F, U, C, V: Value> StreamCallable<C, V> for F
+where
+    F: Fn(&mut C, Arguments<V>) -> U + Clone,
+    U: Stream,
+    U::Item: Resultable,
+    <U::Item as Resultable>::Error: Into<Error<V>>,
+    <U::Item as Resultable>::Ok: Into<V> + Typed<V>,
+{
+    type Stream<'a>
+        = FuncStream<U, V>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        Signature::stream(
+            Parameters::new(),
+            <<U::Item as Resultable>::Ok as Typed<V>>::get_type(),
+        )
+    }
+
+    fn call_stream<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Stream<'a> {
+        FuncStream::new((self)(ctx, args))
+    }
+}
+
+pin_project! {
+    /// Converts the items of a user stream into results of `V`.
+    /// Fails with a single error if the stream could not be opened
+    #[project = FuncStreamProj]
+    pub enum FuncStream<S, V: Value> {
+        Error {
+            error: Option<Error<V>>,
+        },
+        Stream {
+            #[pin]
+            stream: S,
+        },
+    }
+}
+
+impl<S, V: Value> FuncStream<S, V> {
+    pub fn new(stream: S) -> FuncStream<S, V> {
+        FuncStream::Stream { stream }
+    }
+
+    pub fn error(error: Error<V>) -> FuncStream<S, V> {
+        FuncStream::Error { error: Some(error) }
+    }
+}
+
+impl<S, V> Stream for FuncStream<S, V>
+where
+    S: Stream,
+    S::Item: Resultable,
+    <S::Item as Resultable>::Error: Into<Error<V>>,
+    <S::Item as Resultable>::Ok: Into<V>,
+    V: Value,
+{
+    type Item = Result<V, Error<V>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.project() {
+            FuncStreamProj::Error { error } => Poll::Ready(error.take().map(Err)),
+            FuncStreamProj::Stream { stream } => match stream.poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    Poll::Ready(Some(item.into_result().map(Into::into).map_err(Into::into)))
+                }
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
+
+pin_project! {
+    /// Waits for an async function to open its stream, then yields like `FuncStream`
+    #[project = AsyncFuncStreamProj]
+    pub enum AsyncFuncStream<F, S, V: Value> {
+        Open {
+            #[pin]
+            future: F,
+        },
+        Stream {
+            #[pin]
+            stream: FuncStream<S, V>,
+        },
+    }
+}
+
+impl<F, S, V: Value> AsyncFuncStream<F, S, V> {
+    pub fn new(future: F) -> AsyncFuncStream<F, S, V> {
+        AsyncFuncStream::Open { future }
+    }
+
+    pub fn error(error: Error<V>) -> AsyncFuncStream<F, S, V> {
+        AsyncFuncStream::Stream {
+            stream: FuncStream::error(error),
+        }
+    }
+}
+
+impl<F, S, V> Stream for AsyncFuncStream<F, S, V>
+where
+    F: Future<Output = S>,
+    S: Stream,
+    S::Item: Resultable,
+    <S::Item as Resultable>::Error: Into<Error<V>>,
+    <S::Item as Resultable>::Ok: Into<V>,
+    V: Value,
+{
+    type Item = Result<V, Error<V>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().project() {
+                AsyncFuncStreamProj::Open { future } => {
+                    let stream = ready!(future.poll(cx));
+                    self.set(AsyncFuncStream::Stream {
+                        stream: FuncStream::new(stream),
+                    });
+                }
+                AsyncFuncStreamProj::Stream { stream } => return stream.poll_next(cx),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use futures::{executor::block_on_stream, stream};
+
+    use super::*;
+    use crate::{arguments::ToArguments, testing::Value, FuncExt};
+
+    fn collect<S: Stream<Item = Result<Value, Error<Value>>> + Unpin>(
+        stream: S,
+    ) -> Vec<Result<Value, Error<Value>>> {
+        block_on_stream(stream).collect()
+    }
+
+    fn count(_ctx: &mut (), to: i64) -> impl Stream<Item = i64> {
+        stream::iter(1..=to)
+    }
+
+    #[test]
+    fn functions_returning_streams() {
+        let callable = count.callable::<Value>();
+        assert!(callable.signature().is_stream());
+
+        let stream = callable.call_stream(&mut (), (2i64,).to_arguments());
+        let items = collect(Box::pin(stream));
+        assert_eq!(
+            items.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+            [Value::Int(1), Value::Int(2)]
+        );
+    }
+
+    #[test]
+    fn async_functions_resolving_to_streams() {
+        let count = |_ctx: &mut (), to: i64| async move { stream::iter(1..=to) };
+        let callable = count.callable::<Value>().async_stream();
+        assert!(callable.signature().is_stream());
+
+        let stream = callable.call_stream(&mut (), (3i64,).to_arguments());
+        let items = collect(Box::pin(stream));
+        assert_eq!(
+            items.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+            [Value::Int(1), Value::Int(2), Value::Int(3)]
+        );
+    }
+
+    #[test]
+    fn argument_errors_end_the_stream() {
+        let callable = count.callable::<Value>();
+        let stream = callable.call_stream(&mut (), ("two",).to_arguments());
+        let items = collect(Box::pin(stream));
+        assert!(matches!(items[..], [Err(Error::Argument(_))]));
+    }
+}
@@ -0,0 +1,213 @@
+use alloc::sync::Arc;
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+};
+use event_listener::{Event, EventListener};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{arguments::Arguments, signature::Signature, AsyncCallable, Error, Value};
+
+struct Inner {
+    cancelled: AtomicBool,
+    event: Event,
+}
+
+/// A token shared between the caller and in-flight calls.
+/// Cancelling the token fails every call wrapped with it with `Error::Cancelled`
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                event: Event::new(),
+            }),
+        }
+    }
+
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::SeqCst) {
+            self.inner.event.notify(usize::MAX);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves when the token is cancelled
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+            listener: None,
+        }
+    }
+
+    /// Abort `future` with `Error::Cancelled` when the token is cancelled
+    pub fn run<F>(&self, future: F) -> CancellableFuture<F> {
+        CancellableFuture {
+            future,
+            cancelled: self.cancelled(),
+        }
+    }
+}
+
+pub struct Cancelled {
+    token: CancellationToken,
+    listener: Option<EventListener>,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            if self.token.is_cancelled() {
+                return Poll::Ready(());
+            }
+
+            match self.listener.as_mut() {
+                Some(listener) => {
+                    ready!(Pin::new(listener).poll(cx));
+                    self.listener = None;
+                }
+                None => {
+                    // Check again after registering, so a cancel in between is not lost
+                    self.listener = Some(self.token.inner.event.listen());
+                }
+            }
+        }
+    }
+}
+
+pin_project! {
+    pub struct CancellableFuture<F> {
+        #[pin]
+        future: F,
+        cancelled: Cancelled,
+    }
+}
+
+impl<F, V> Future for CancellableFuture<F>
+where
+    F: Future<Output = Result<V, Error<V>>>,
+    V: Value,
+{
+    type Output = Result<V, Error<V>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if Pin::new(this.cancelled).poll(cx).is_ready() {
+            return Poll::Ready(Err(Error::Cancelled));
+        }
+
+        this.future.poll(cx)
+    }
+}
+
+/// An async callable which is aborted when its token is cancelled
+#[derive(Clone)]
+pub struct Cancellable<F> {
+    callable: F,
+    token: CancellationToken,
+}
+
+impl<F> Cancellable<F> {
+    pub fn new(callable: F, token: CancellationToken) -> Cancellable<F> {
+        Cancellable { callable, token }
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl<F, C, V> AsyncCallable<C, V> for Cancellable<F>
+where
+    F: AsyncCallable<C, V>,
+    V: Value,
+{
+    type Future<'a>
+        = CancellableFuture<F::Future<'a>>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call_async<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Future<'a> {
+        self.token.run(self.callable.call_async(ctx, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use futures::{executor::block_on, future::join, FutureExt};
+
+    use super::*;
+    use crate::{
+        arguments::ToArguments,
+        signature::Parameters,
+        testing::{Type, Value},
+    };
+
+    /// Never completes
+    struct Pending;
+
+    impl AsyncCallable<(), Value> for Pending {
+        type Future<'a> = Pin<Box<dyn Future<Output = Result<Value, Error<Value>>> + 'a>>;
+
+        fn signature(&self) -> Signature<Value> {
+            Signature::new(Parameters::new(), Type::Void)
+        }
+
+        fn call_async<'a>(&'a self, _ctx: &'a mut (), _args: Arguments<Value>) -> Self::Future<'a> {
+            Box::pin(core::future::pending())
+        }
+    }
+
+    #[test]
+    fn in_flight_calls_are_cancelled() {
+        let token = CancellationToken::new();
+        let callable = Cancellable::new(Pending, token.clone());
+        let mut ctx = ();
+
+        // The call is polled, and left pending, before the token is cancelled
+        let (ret, ()) = block_on(join(
+            callable.call_async(&mut ctx, ().to_arguments()),
+            async { token.cancel() },
+        ));
+        assert!(matches!(ret, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn cancelled_tokens_fail_calls_at_once() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let callable = Cancellable::new(Pending, token);
+        let mut ctx = ();
+        let ret = callable
+            .call_async(&mut ctx, ().to_arguments())
+            .now_or_never();
+        assert!(matches!(ret, Some(Err(Error::Cancelled))));
+    }
+}
@@ -0,0 +1,273 @@
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use super::{Error, Frame};
+use crate::traits::Value;
+
+/// What went wrong, independent of the enabled features
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    Argument,
+    Runtime,
+    MethodNotFound,
+    StaleMethod,
+    Lock,
+    UnknownField,
+    Timeout,
+    Cancelled,
+    Overloaded,
+    RateLimited,
+    NameTaken,
+    Infallible,
+}
+
+impl ErrorKind {
+    /// The default code for errors of this kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::Argument => "argument",
+            ErrorKind::Runtime => "runtime",
+            ErrorKind::MethodNotFound => "method_not_found",
+            ErrorKind::StaleMethod => "stale_method",
+            ErrorKind::Lock => "lock",
+            ErrorKind::UnknownField => "unknown_field",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Cancelled => "cancelled",
+            ErrorKind::Overloaded => "overloaded",
+            ErrorKind::RateLimited => "rate_limited",
+            ErrorKind::NameTaken => "name_taken",
+            ErrorKind::Infallible => "infallible",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Errors with a stable code, which survives serialization.
+/// Turn one into an `Error` with `Error::coded`
+pub trait ErrorCode<V>: core::error::Error + Send + Sync + 'static {
+    fn code(&self) -> Cow<'static, str>;
+
+    /// Extra data for the receiver, like the offending value
+    fn data(&self) -> Option<V> {
+        None
+    }
+}
+
+/// A plain representation of an `Error`, for sending it to another process.
+///
+/// Converting it back gives an `Error::Remote`, which converts to the same representation
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "V: serde::Serialize",
+        deserialize = "V: serde::Deserialize<'de>"
+    ))
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorRepr<V> {
+    pub kind: ErrorKind,
+    pub code: String,
+    pub message: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub data: Option<V>,
+    /// Context frames, outermost first
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub context: Vec<Frame>,
+}
+
+impl<V> fmt::Display for ErrorRepr<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for frame in &self.context {
+            write!(f, "{frame}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl<V: Value> Error<V> {
+    /// A runtime error carrying its own code
+    pub fn coded<E: ErrorCode<V>>(error: E) -> Error<V> {
+        Error::Coded {
+            code: error.code(),
+            data: error.data(),
+            error: alloc::boxed::Box::new(error),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self.root() {
+            Error::Argument(_) => ErrorKind::Argument,
+            Error::Runtime(_) | Error::Coded { .. } => ErrorKind::Runtime,
+            Error::Remote(repr) => repr.kind,
+            Error::Context { .. } => unreachable!("root has no context"),
+            #[cfg(feature = "service")]
+            Error::MethodNotFound => ErrorKind::MethodNotFound,
+            #[cfg(feature = "service")]
+            Error::StaleMethod => ErrorKind::StaleMethod,
+            #[cfg(feature = "service")]
+            Error::Lock => ErrorKind::Lock,
+            #[cfg(feature = "service")]
+            Error::UnknownField(_) => ErrorKind::UnknownField,
+            #[cfg(feature = "async")]
+            Error::Timeout => ErrorKind::Timeout,
+            #[cfg(feature = "async")]
+            Error::Cancelled => ErrorKind::Cancelled,
+            #[cfg(all(feature = "service", feature = "async"))]
+            Error::Overloaded => ErrorKind::Overloaded,
+            #[cfg(all(feature = "service", feature = "async"))]
+            Error::RateLimited => ErrorKind::RateLimited,
+            #[cfg(all(feature = "service", feature = "async"))]
+            Error::NameTaken(_) => ErrorKind::NameTaken,
+            Error::Infallible => ErrorKind::Infallible,
+        }
+    }
+
+    /// The code of the error below all context frames
+    pub fn code(&self) -> &str {
+        match self.root() {
+            Error::Coded { code, .. } => code.as_ref(),
+            Error::Remote(repr) => repr.code.as_str(),
+            err => err.kind().code(),
+        }
+    }
+
+    pub fn into_repr(self) -> ErrorRepr<V> {
+        let mut context = Vec::new();
+        let mut current = self;
+        while let Error::Context { frame, error } = current {
+            context.push(frame);
+            current = *error;
+        }
+
+        let mut repr = match current {
+            Error::Remote(repr) => *repr,
+            Error::Coded { code, error, data } => ErrorRepr {
+                kind: ErrorKind::Runtime,
+                code: code.into_owned(),
+                message: error.to_string(),
+                data,
+                context: Vec::new(),
+            },
+            err => ErrorRepr {
+                kind: err.kind(),
+                code: err.code().into(),
+                message: err.to_string(),
+                data: None,
+                context: Vec::new(),
+            },
+        };
+
+        context.append(&mut repr.context);
+        repr.context = context;
+        repr
+    }
+}
+
+impl<V: Value> From<Error<V>> for ErrorRepr<V> {
+    fn from(value: Error<V>) -> Self {
+        value.into_repr()
+    }
+}
+
+impl<V: Value> From<ErrorRepr<V>> for Error<V> {
+    fn from(value: ErrorRepr<V>) -> Self {
+        Error::Remote(alloc::boxed::Box::new(value))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{arguments::ArgumentError, testing::Value, ErrorContext};
+
+    #[derive(Debug)]
+    struct InsufficientFunds {
+        needed: i64,
+    }
+
+    impl fmt::Display for InsufficientFunds {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "insufficient funds, {} more needed", self.needed)
+        }
+    }
+
+    impl core::error::Error for InsufficientFunds {}
+
+    impl ErrorCode<Value> for InsufficientFunds {
+        fn code(&self) -> Cow<'static, str> {
+            "insufficient_funds".into()
+        }
+
+        fn data(&self) -> Option<Value> {
+            Some(Value::Int(self.needed))
+        }
+    }
+
+    fn round_trip(repr: &ErrorRepr<Value>) -> ErrorRepr<Value> {
+        let json = serde_json::to_string(repr).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn coded_errors_keep_code_data_and_context() {
+        let err = Err::<(), _>(Error::coded(InsufficientFunds { needed: 15 }))
+            .context("monthly payout")
+            .unwrap_err();
+
+        let repr = err.into_repr();
+        assert_eq!(repr.kind, ErrorKind::Runtime);
+        assert_eq!(repr.code, "insufficient_funds");
+        assert_eq!(repr.data, Some(Value::Int(15)));
+        assert_eq!(repr.context, [Frame::Message("monthly payout".into())]);
+
+        let received = round_trip(&repr);
+        assert_eq!(received, repr);
+
+        let err = Error::from(received);
+        assert_eq!(err.code(), "insufficient_funds");
+        assert_eq!(err.to_string(), repr.to_string());
+        assert_eq!(err.into_repr(), repr);
+    }
+
+    #[test]
+    fn kinds_keep_their_default_code() {
+        let err = Error::<Value>::Argument(ArgumentError::Missing { index: 0, arity: 0 });
+        let repr = round_trip(&err.into_repr());
+        assert_eq!(repr.kind, ErrorKind::Argument);
+        assert_eq!(repr.code, "argument");
+        assert_eq!(Error::from(repr).kind(), ErrorKind::Argument);
+    }
+
+    #[test]
+    fn empty_fields_are_skipped() {
+        let repr = Error::<Value>::Infallible.into_repr();
+        let json = serde_json::to_value(&repr).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "infallible",
+                "code": "infallible",
+                "message": repr.message,
+            })
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use alloc::string::String;
+use core::fmt;
+
+/// Where an error passed through on its way to the caller
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Frame {
+    Callable(String),
+    Method(String),
+    Message(String),
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frame::Callable(name) => write!(f, "callable `{name}`"),
+            Frame::Method(name) => write!(f, "method `{name}`"),
+            Frame::Message(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<String> for Frame {
+    fn from(value: String) -> Self {
+        Frame::Message(value)
+    }
+}
+
+impl<'a> From<&'a str> for Frame {
+    fn from(value: &'a str) -> Self {
+        Frame::Message(value.into())
+    }
+}
@@ -0,0 +1,244 @@
+mod code;
+mod frame;
+
+use core::fmt::Debug;
+
+use crate::{arguments::ArgumentError, traits::Value};
+use alloc::{borrow::Cow, boxed::Box, fmt};
+#[cfg(feature = "service")]
+use alloc::string::String;
+
+pub use self::{
+    code::{ErrorCode, ErrorKind, ErrorRepr},
+    frame::Frame,
+};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error<V: Value> {
+    Argument(ArgumentError<V>),
+    Runtime(Box<dyn core::error::Error + Send + Sync>),
+    /// A runtime error with its own code, see `ErrorCode`
+    Coded {
+        code: Cow<'static, str>,
+        error: Box<dyn core::error::Error + Send + Sync>,
+        data: Option<V>,
+    },
+    /// An error received from elsewhere, as it was reported
+    Remote(Box<ErrorRepr<V>>),
+    /// An error annotated with where it passed through.
+    /// Wrapping changes the top-level variant, so match on `Error::root` to see the cause
+    Context {
+        frame: Frame,
+        error: Box<Error<V>>,
+    },
+    /// Services report it in a `Frame::Method` naming the method
+    #[cfg(feature = "service")]
+    MethodNotFound,
+    /// The method behind a `MethodId` was registered again; resolve it anew
+    #[cfg(feature = "service")]
+    StaleMethod,
+    #[cfg(feature = "service")]
+    Lock,
+    #[cfg(feature = "service")]
+    UnknownField(String),
+    #[cfg(feature = "async")]
+    Timeout,
+    #[cfg(feature = "async")]
+    Cancelled,
+    #[cfg(all(feature = "service", feature = "async"))]
+    Overloaded,
+    #[cfg(all(feature = "service", feature = "async"))]
+    RateLimited,
+    /// A service name is already taken by a method or a stream, which share the signatures
+    #[cfg(all(feature = "service", feature = "async"))]
+    NameTaken(String),
+    Infallible,
+}
+
+impl<V: Value> Error<V> {
+    pub fn new<E: Into<Box<dyn core::error::Error + Send + Sync>>>(error: E) -> Error<V> {
+        Error::Runtime(error.into())
+    }
+
+    /// The runtime error below all context frames, if it is an `E`
+    pub fn downcast_ref<E: core::error::Error + 'static>(&self) -> Option<&E> {
+        match self.root() {
+            Error::Runtime(err) | Error::Coded { error: err, .. } => err.downcast_ref::<E>(),
+            _ => None,
+        }
+    }
+
+    pub fn is<E: core::error::Error + 'static>(&self) -> bool {
+        self.downcast_ref::<E>().is_some()
+    }
+
+    /// Wrap the error in a context frame
+    pub fn context(self, frame: impl Into<Frame>) -> Error<V> {
+        Error::Context {
+            frame: frame.into(),
+            error: Box::new(self),
+        }
+    }
+
+    /// The context frames, outermost first
+    pub fn frames(&self) -> Frames<'_, V> {
+        Frames { current: self }
+    }
+
+    /// The error below all context frames
+    pub fn root(&self) -> &Error<V> {
+        let mut current = self;
+        while let Error::Context { error, .. } = current {
+            current = error;
+        }
+        current
+    }
+}
+
+pub struct Frames<'a, V: Value> {
+    current: &'a Error<V>,
+}
+
+impl<'a, V: Value> Iterator for Frames<'a, V> {
+    type Item = &'a Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.current {
+            Error::Context { frame, error } => {
+                self.current = error;
+                Some(frame)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Add context frames to results
+pub trait ErrorContext<T, V: Value> {
+    fn context(self, frame: impl Into<Frame>) -> Result<T, Error<V>>;
+
+    fn with_context<F, R>(self, frame: F) -> Result<T, Error<V>>
+    where
+        F: FnOnce() -> R,
+        R: Into<Frame>;
+}
+
+impl<T, V: Value> ErrorContext<T, V> for Result<T, Error<V>> {
+    fn context(self, frame: impl Into<Frame>) -> Result<T, Error<V>> {
+        self.map_err(|err| err.context(frame))
+    }
+
+    fn with_context<F, R>(self, frame: F) -> Result<T, Error<V>>
+    where
+        F: FnOnce() -> R,
+        R: Into<Frame>,
+    {
+        self.map_err(|err| err.context(frame()))
+    }
+}
+
+impl<V: Value> From<ArgumentError<V>> for Error<V> {
+    fn from(value: ArgumentError<V>) -> Self {
+        Error::Argument(value)
+    }
+}
+
+impl<V: Value + Debug> From<core::convert::Infallible> for Error<V> {
+    fn from(_value: core::convert::Infallible) -> Self {
+        Error::Infallible
+    }
+}
+
+impl<V: Value> fmt::Display for Error<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Argument(a) => write!(f, "{}", a),
+            Error::Runtime(e) => write!(f, "{e}"),
+            Error::Coded { error, .. } => write!(f, "{error}"),
+            Error::Remote(repr) => write!(f, "{repr}"),
+            // Reads outermost first: "method `add`: argument 1: invalid type. ..."
+            Error::Context { frame, error } => write!(f, "{frame}: {error}"),
+            #[cfg(feature = "service")]
+            Error::MethodNotFound => write!(f, "method not found"),
+            #[cfg(feature = "service")]
+            Error::StaleMethod => write!(f, "stale method handle"),
+            Error::Infallible => write!(f, "infallible"),
+            #[cfg(feature = "async")]
+            Error::Timeout => write!(f, "timeout"),
+            #[cfg(feature = "async")]
+            Error::Cancelled => write!(f, "cancelled"),
+            #[cfg(all(feature = "service", feature = "async"))]
+            Error::Overloaded => write!(f, "too many concurrent calls"),
+            #[cfg(all(feature = "service", feature = "async"))]
+            Error::RateLimited => write!(f, "rate limited"),
+            #[cfg(all(feature = "service", feature = "async"))]
+            Error::NameTaken(name) => write!(f, "`{name}` is already registered"),
+            #[cfg(feature = "service")]
+            Error::Lock => write!(f, "lock"),
+            #[cfg(feature = "service")]
+            Error::UnknownField(name) => write!(f, "unknown field: {name}"),
+        }
+    }
+}
+
+// Display already includes the wrapped error, so sources skip ahead to what it does not show
+impl<V: Value + 'static> core::error::Error for Error<V> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Runtime(e) | Error::Coded { error: e, .. } => e.source(),
+            Error::Context { error, .. } => error.source(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "service")]
+impl<V: Value> From<locket::LockError> for Error<V> {
+    fn from(_value: locket::LockError) -> Self {
+        Error::Lock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::*;
+    use crate::testing::Value;
+
+    #[test]
+    fn frames_are_listed_outermost_first() {
+        let err: Error<Value> = Error::new("boom")
+            .context("inner")
+            .context(Frame::Method("add".into()));
+
+        assert_eq!(
+            err.frames().cloned().collect::<Vec<_>>(),
+            [Frame::Method("add".into()), Frame::Message("inner".into())]
+        );
+        assert_eq!(err.to_string(), "method `add`: inner: boom");
+    }
+
+    #[test]
+    fn root_skips_every_frame() {
+        let err: Error<Value> = Error::Infallible.context("a").context("b");
+        assert!(matches!(err, Error::Context { .. }));
+        assert!(matches!(err.root(), Error::Infallible));
+
+        let err: Error<Value> = Error::Infallible;
+        assert!(err.frames().next().is_none());
+        assert!(matches!(err.root(), Error::Infallible));
+    }
+
+    #[test]
+    fn result_context_is_lazy() {
+        let ok: Result<(), Error<Value>> = Ok(());
+        let ret = ok.with_context(|| -> Frame { unreachable!() });
+        assert!(ret.is_ok());
+
+        let err: Result<(), Error<Value>> = Err(Error::new("boom"));
+        let err = err.with_context(|| "outer").unwrap_err();
+        assert_eq!(err.to_string(), "outer: boom");
+    }
+}
@@ -0,0 +1,305 @@
+use alloc::boxed::Box;
+use core::pin::Pin;
+use futures_core::Future;
+
+pub trait Executor {
+    type Error;
+    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
+        func: F,
+    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>>;
+}
+
+#[cfg(feature = "tokio")]
+pub struct Tokio;
+
+#[cfg(feature = "tokio")]
+impl Executor for Tokio {
+    type Error = tokio::task::JoinError;
+    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
+        func: F,
+    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
+        Box::pin(tokio::task::spawn_blocking(func))
+    }
+}
+
+#[cfg(feature = "smol")]
+pub struct Smol;
+
+#[cfg(feature = "smol")]
+impl Executor for Smol {
+    type Error = core::convert::Infallible;
+    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
+        func: F,
+    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
+        Box::pin(async move { Ok(smol::unblock(func).await) })
+    }
+}
+
+#[cfg(feature = "async-std")]
+pub struct AsyncStd;
+
+#[cfg(feature = "async-std")]
+impl Executor for AsyncStd {
+    type Error = core::convert::Infallible;
+    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
+        func: F,
+    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
+        Box::pin(async move { Ok(async_std::task::spawn_blocking(func).await) })
+    }
+}
+
+#[cfg(feature = "blocking")]
+pub struct Blocking;
+
+#[cfg(feature = "blocking")]
+impl Executor for Blocking {
+    type Error = core::convert::Infallible;
+    fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
+        func: F,
+    ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
+        Box::pin(async move { Ok(blocking::unblock(func).await) })
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::thread::{Thread, ThreadPanic};
+
+#[cfg(feature = "std")]
+mod thread {
+    use super::Executor;
+    use alloc::{boxed::Box, sync::Arc};
+    use core::{
+        fmt,
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+    use futures_core::Future;
+    use std::{
+        panic::{catch_unwind, AssertUnwindSafe},
+        sync::Mutex,
+    };
+
+    /// Runs every call on a new OS thread.
+    /// Useful when no async runtime is available.
+    pub struct Thread;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ThreadPanic;
+
+    impl fmt::Display for ThreadPanic {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "thread panicked")
+        }
+    }
+
+    impl std::error::Error for ThreadPanic {}
+
+    struct Slot<R> {
+        value: Option<Result<R, ThreadPanic>>,
+        waker: Option<Waker>,
+    }
+
+    struct ThreadFuture<R> {
+        slot: Arc<Mutex<Slot<R>>>,
+    }
+
+    impl<R> Future for ThreadFuture<R> {
+        type Output = Result<R, ThreadPanic>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut slot = match self.slot.lock() {
+                Ok(slot) => slot,
+                Err(_) => return Poll::Ready(Err(ThreadPanic)),
+            };
+
+            match slot.value.take() {
+                Some(ret) => Poll::Ready(ret),
+                None => {
+                    slot.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    impl Executor for Thread {
+        type Error = ThreadPanic;
+        fn spawn_blocking<F: FnOnce() -> R + 'static + Send, R: Send + 'static>(
+            func: F,
+        ) -> Pin<Box<dyn Future<Output = Result<R, Self::Error>> + Send>> {
+            let slot = Arc::new(Mutex::new(Slot {
+                value: None,
+                waker: None,
+            }));
+
+            let thread_slot = slot.clone();
+            std::thread::spawn(move || {
+                let ret = catch_unwind(AssertUnwindSafe(func)).map_err(|_| ThreadPanic);
+                if let Ok(mut slot) = thread_slot.lock() {
+                    slot.value = Some(ret);
+                    if let Some(waker) = slot.waker.take() {
+                        waker.wake();
+                    }
+                }
+            });
+
+            Box::pin(ThreadFuture { slot })
+        }
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "tokio",
+        feature = "smol",
+        feature = "async-std",
+        feature = "blocking",
+        feature = "std"
+    )
+))]
+mod tests {
+    use crate::{
+        arguments::ToArguments, testing::Value, AsyncCallable, Callable, CallableExt, FuncExt,
+    };
+
+    fn add(ctx: &mut i64, a: i64, b: i64) -> i64 {
+        *ctx += 1;
+        a + b
+    }
+
+    #[cfg(any(feature = "tokio", feature = "std"))]
+    fn panics(_ctx: &mut i64) -> i64 {
+        panic!("callable panicked")
+    }
+
+    fn add_callable() -> impl Callable<i64, Value> + Send + Sync + 'static {
+        (add as fn(&mut i64, i64, i64) -> i64).callable::<Value>()
+    }
+
+    #[cfg(any(feature = "tokio", feature = "std"))]
+    fn panics_callable() -> impl Callable<i64, Value> + Send + Sync + 'static {
+        (panics as fn(&mut i64) -> i64).callable::<Value>()
+    }
+
+    /// Calls `add` twice on the executor, checking the context comes back both times
+    async fn hands_back_the_context<C: AsyncCallable<Option<i64>, Value>>(callable: C) {
+        let mut calls = Some(0);
+        let ret = callable
+            .call_async(&mut calls, (1i64, 2i64).to_arguments())
+            .await
+            .unwrap();
+        assert_eq!(ret, Value::Int(3));
+        let ret = callable
+            .call_async(&mut calls, (3i64, 4i64).to_arguments())
+            .await
+            .unwrap();
+        assert_eq!(ret, Value::Int(7));
+        assert_eq!(calls, Some(2));
+    }
+
+    #[cfg(feature = "tokio")]
+    mod tokio {
+        use super::*;
+        use crate::{Error, Tokio};
+
+        fn runtime() -> ::tokio::runtime::Runtime {
+            ::tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+        }
+
+        #[test]
+        fn runs_the_callable() {
+            runtime().block_on(hands_back_the_context(add_callable().into_async::<Tokio>()));
+        }
+
+        #[test]
+        fn panics_become_runtime_errors() {
+            let callable = panics_callable().into_async::<Tokio>();
+            let mut ctx = Some(0);
+            let ret = runtime().block_on(callable.call_async(&mut ctx, ().to_arguments()));
+            assert!(matches!(ret, Err(Error::Runtime(_))));
+        }
+    }
+
+    #[cfg(feature = "smol")]
+    #[test]
+    fn smol() {
+        smol::block_on(hands_back_the_context(
+            add_callable().into_async::<crate::Smol>(),
+        ));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn async_std() {
+        async_std::task::block_on(hands_back_the_context(
+            add_callable().into_async::<crate::AsyncStd>(),
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking() {
+        futures::executor::block_on(hands_back_the_context(
+            add_callable().into_async::<crate::Blocking>(),
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    mod thread {
+        use super::*;
+        use crate::{Error, Thread, ThreadPanic};
+
+        #[test]
+        fn runs_the_callable() {
+            futures::executor::block_on(hands_back_the_context(
+                add_callable().into_async::<Thread>(),
+            ));
+        }
+
+        #[test]
+        fn panics_become_runtime_errors() {
+            let callable = panics_callable().into_async::<Thread>();
+            let mut ctx = Some(1);
+            let ret = futures::executor::block_on(callable.call_async(&mut ctx, ().to_arguments()));
+            match ret {
+                Err(Error::Runtime(err)) => assert!(err.downcast_ref::<ThreadPanic>().is_some()),
+                ret => panic!("expected a runtime error, got {ret:?}"),
+            }
+            // The panic is caught before it reaches the executor, so the context comes back
+            assert_eq!(ctx, Some(1));
+        }
+
+        #[test]
+        fn dropped_calls_keep_the_context() {
+            let callable = add_callable().into_async::<Thread>();
+            let mut ctx = Some(0);
+            drop(callable.call_async(&mut ctx, (1i64, 2i64).to_arguments()));
+            assert_eq!(ctx, Some(0));
+
+            // Polled once, the context is on the executor and is lost with the future
+            let mut future = callable.call_async(&mut ctx, (1i64, 2i64).to_arguments());
+            let _ = futures::executor::block_on(async { futures::poll!(&mut future) });
+            drop(future);
+            assert_eq!(ctx, None);
+
+            let ret = futures::executor::block_on(
+                callable.call_async(&mut ctx, (1i64, 2i64).to_arguments()),
+            );
+            assert!(ret.unwrap_err().is::<crate::ContextMissing>());
+        }
+
+        #[test]
+        fn shared_context_is_locked_for_the_call() {
+            let callable = add_callable().into_async_shared::<Thread>();
+            let mut calls = std::sync::Arc::new(std::sync::Mutex::new(0));
+            let ret = futures::executor::block_on(
+                callable.call_async(&mut calls, (1i64, 2i64).to_arguments()),
+            );
+            assert_eq!(ret.unwrap(), Value::Int(3));
+            assert_eq!(*calls.lock().unwrap(), 1);
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use core::marker::PhantomData;
+
+use crate::{
+    func::{Func, SharedFunc},
+    Error, Resultable, Value,
+};
+
+#[cfg(feature = "async")]
+use crate::func::{AsyncFunc, AsyncSharedFunc};
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "async")]
+use pin_project_lite::pin_project;
+
+/// Pulls a value out of the context, like a database handle or the current user
+pub trait FromContext<C, V: Value>: Sized {
+    fn from_context(ctx: &C) -> Result<Self, Error<V>>;
+}
+
+impl<C, V: Value> FromContext<C, V> for () {
+    fn from_context(_ctx: &C) -> Result<Self, Error<V>> {
+        Ok(())
+    }
+}
+
+/// Extracts `None` when `T` fails, which drops its error.
+/// Take `T` itself to reject the call instead
+impl<C, V: Value, T> FromContext<C, V> for Option<T>
+where
+    T: FromContext<C, V>,
+{
+    fn from_context(ctx: &C) -> Result<Self, Error<V>> {
+        Ok(T::from_context(ctx).ok())
+    }
+}
+
+macro_rules! from_context {
+    ($first: ident) => {
+        impl<C, V: Value, $first: FromContext<C, V>> FromContext<C, V> for ($first,) {
+            fn from_context(ctx: &C) -> Result<Self, Error<V>> {
+                Ok(($first::from_context(ctx)?,))
+            }
+        }
+    };
+    ($first: ident $($rest: ident)*) => {
+        from_context!($($rest)*);
+
+        impl<C, V: Value, $first: FromContext<C, V>, $($rest: FromContext<C, V>),*> FromContext<C, V> for ($first, $($rest),*) {
+            fn from_context(ctx: &C) -> Result<Self, Error<V>> {
+                Ok(($first::from_context(ctx)?, $($rest::from_context(ctx)?),*))
+            }
+        }
+    };
+}
+
+from_context!(T1 T2 T3 T4 T5 T6 T7 T8);
+
+/// The output of an extracting function; either its return value or the extraction error
+pub enum Extracted<U, V: Value> {
+    Ok(U),
+    Rejected(Error<V>),
+}
+
+impl<U, V> Resultable for Extracted<U, V>
+where
+    U: Resultable,
+    U::Error: Into<Error<V>>,
+    V: Value,
+{
+    type Ok = U::Ok;
+    type Error = Error<V>;
+
+    fn into_result(self) -> Result<<Extracted<U, V> as Resultable>::Ok, Self::Error> {
+        match self {
+            Extracted::Ok(ret) => ret.into_result().map_err(Into::into),
+            Extracted::Rejected(err) => Err(err),
+        }
+    }
+}
+
+/// Adapts a function whose first parameter is extracted from the context,
+/// like `fn list(db: Db, page: u32)`. The remaining parameters are the arguments of the call,
+/// so only those are part of the signature.
+/// Use a tuple to extract more than one value: `fn list((db, user): (Db, User), page: u32)`
+pub struct Extract<F, X, V> {
+    func: F,
+    _extract: PhantomData<fn() -> (X, V)>,
+}
+
+impl<F: Clone, X, V> Clone for Extract<F, X, V> {
+    fn clone(&self) -> Self {
+        Extract {
+            func: self.func.clone(),
+            _extract: PhantomData,
+        }
+    }
+}
+
+impl<F: Copy, X, V> Copy for Extract<F, X, V> {}
+
+impl<F, X, V> Extract<F, X, V> {
+    pub fn new(func: F) -> Extract<F, X, V> {
+        Extract {
+            func,
+            _extract: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> F {
+        self.func
+    }
+}
+
+pub fn extract<F, X, V>(func: F) -> Extract<F, X, V> {
+    Extract::new(func)
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    #[project = ExtractProj]
+    pub enum ExtractFuture<U, V: Value> {
+        Rejected {
+            error: Option<Error<V>>,
+        },
+        Future {
+            #[pin]
+            future: U,
+        },
+    }
+}
+
+#[cfg(feature = "async")]
+impl<U, V> Future for ExtractFuture<U, V>
+where
+    U: Future,
+    V: Value,
+{
+    type Output = Extracted<U::Output, V>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ExtractProj::Rejected { error } => {
+                Poll::Ready(Extracted::Rejected(error.take().expect("poll after done")))
+            }
+            ExtractProj::Future { future } => future.poll(cx).map(Extracted::Ok),
+        }
+    }
+}
+
+macro_rules! extract_funcs {
+    (@impl $($ty: ident)*) => {
+        impl<F, X, C, U, V, $($ty),*> Func<C, ($($ty,)*)> for Extract<F, X, V>
+        where
+            F: Fn(X, $($ty),*) -> U + 'static,
+            X: FromContext<C, V>,
+            V: Value,
+        {
+            type Output = Extracted<U, V>;
+
+            fn call(&self, ctx: &mut C, input: ($($ty,)*)) -> Self::Output {
+                #[allow(non_snake_case)]
+                let ($($ty,)*) = input;
+                match X::from_context(ctx) {
+                    Ok(extracted) => Extracted::Ok((self.func)(extracted, $($ty),*)),
+                    Err(err) => Extracted::Rejected(err),
+                }
+            }
+        }
+
+        impl<F, X, C, U, V, $($ty),*> SharedFunc<C, ($($ty,)*)> for Extract<F, X, V>
+        where
+            F: Fn(X, $($ty),*) -> U + 'static,
+            X: FromContext<C, V>,
+            V: Value,
+        {
+            type Output = Extracted<U, V>;
+
+            fn call(&self, ctx: &C, input: ($($ty,)*)) -> Self::Output {
+                #[allow(non_snake_case)]
+                let ($($ty,)*) = input;
+                match X::from_context(ctx) {
+                    Ok(extracted) => Extracted::Ok((self.func)(extracted, $($ty),*)),
+                    Err(err) => Extracted::Rejected(err),
+                }
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<F, X, C, U, V, $($ty),*> AsyncFunc<C, ($($ty,)*)> for Extract<F, X, V>
+        where
+            F: Fn(X, $($ty),*) -> U + 'static,
+            X: FromContext<C, V>,
+            U: Future,
+            for<'a> U: 'a,
+            V: Value + 'static,
+        {
+            type Output = Extracted<U::Output, V>;
+            type Future<'a> = ExtractFuture<U, V> where X: 'a, C: 'a;
+
+            fn call<'a>(&'a self, ctx: &'a mut C, input: ($($ty,)*)) -> Self::Future<'a> {
+                #[allow(non_snake_case)]
+                let ($($ty,)*) = input;
+                match X::from_context(ctx) {
+                    Ok(extracted) => ExtractFuture::Future {
+                        future: (self.func)(extracted, $($ty),*),
+                    },
+                    Err(err) => ExtractFuture::Rejected { error: Some(err) },
+                }
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<F, X, C, U, V, $($ty),*> AsyncSharedFunc<C, ($($ty,)*)> for Extract<F, X, V>
+        where
+            F: Fn(X, $($ty),*) -> U + 'static,
+            X: FromContext<C, V>,
+            U: Future,
+            for<'a> U: 'a,
+            V: Value + 'static,
+        {
+            type Output = Extracted<U::Output, V>;
+            type Future<'a> = ExtractFuture<U, V> where X: 'a, C: 'a;
+
+            fn call<'a>(&'a self, ctx: &'a C, input: ($($ty,)*)) -> Self::Future<'a> {
+                #[allow(non_snake_case)]
+                let ($($ty,)*) = input;
+                match X::from_context(ctx) {
+                    Ok(extracted) => ExtractFuture::Future {
+                        future: (self.func)(extracted, $($ty),*),
+                    },
+                    Err(err) => ExtractFuture::Rejected { error: Some(err) },
+                }
+            }
+        }
+    };
+    ($first: ident) => {
+        extract_funcs!(@impl);
+        extract_funcs!(@impl $first);
+    };
+    ($first: ident $($rest: ident)*) => {
+        extract_funcs!($($rest)*);
+        extract_funcs!(@impl $first $($rest)*);
+    };
+}
+
+extract_funcs!(T1 T2 T3 T4 T5 T6 T7 T8);
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::*;
+    use crate::{
+        arguments::ToArguments,
+        testing::{Type, Value},
+        Callable, FuncExt,
+    };
+
+    struct Session {
+        user: Option<&'static str>,
+    }
+
+    struct User(&'static str);
+
+    impl FromContext<Session, Value> for User {
+        fn from_context(ctx: &Session) -> Result<Self, Error<Value>> {
+            ctx.user
+                .map(User)
+                .ok_or_else(|| Error::new("not logged in"))
+        }
+    }
+
+    fn greet(user: User, greeting: &str) -> String {
+        format!("{greeting}, {}", user.0)
+    }
+
+    #[test]
+    fn functions_pull_values_from_the_context() {
+        let callable = extract(greet).callable::<Value>();
+        let mut ctx = Session { user: Some("ada") };
+        let ret = callable.call(&mut ctx, ("Hello",).to_arguments());
+        assert_eq!(ret.unwrap(), Value::from("Hello, ada"));
+    }
+
+    #[test]
+    fn extracted_parameters_are_not_in_the_signature() {
+        let signature = extract(greet).callable::<Value>().signature();
+        let params = signature.params().iter().collect::<Vec<_>>();
+        assert_eq!(params, [&Type::String]);
+        assert_eq!(signature.return_type(), Some(&Type::String));
+    }
+
+    #[test]
+    fn failed_extractions_reject_the_call() {
+        let callable = extract(greet).callable::<Value>();
+        let mut ctx = Session { user: None };
+        let err = callable
+            .call(&mut ctx, ("Hello",).to_arguments())
+            .unwrap_err();
+        assert!(matches!(err.root(), Error::Runtime(_)));
+        assert_eq!(err.to_string(), "not logged in");
+    }
+
+    #[test]
+    fn optional_extractions_drop_the_error() {
+        let whoami = |user: Option<User>| user.map_or("nobody", |user| user.0).to_string();
+        let callable = extract(whoami).callable::<Value>();
+        let ret = callable.call(&mut Session { user: None }, Default::default());
+        assert_eq!(ret.unwrap(), Value::from("nobody"));
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use futures::executor::block_on;
+
+        use super::*;
+        use crate::{AsyncCallable, AsyncSharedCallable, SharedFuncExt};
+
+        async fn welcome(user: User, times: i64) -> String {
+            user.0.repeat(times as usize)
+        }
+
+        #[test]
+        fn async_functions_pull_values_from_the_context() {
+            let callable = extract(welcome).callable::<Value>();
+            let mut ctx = Session { user: Some("ada") };
+            let future = callable.call_async(&mut ctx, (2i64,).to_arguments());
+            assert_eq!(block_on(future).unwrap(), Value::from("adaada"));
+
+            let params = AsyncCallable::signature(&callable).params().iter().count();
+            assert_eq!(params, 1);
+        }
+
+        #[test]
+        fn async_functions_take_a_shared_context() {
+            let callable = extract(welcome).shared_callable::<Value>();
+            let future = callable.call_async(&Session { user: None }, (2i64,).to_arguments());
+            let err = block_on(future).unwrap_err();
+            assert_eq!(err.to_string(), "not logged in");
+        }
+    }
+}
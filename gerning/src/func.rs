@@ -4,53 +4,47 @@ pub trait Func<C, T> {
     fn call(&self, ctx: &mut C, input: T) -> Self::Output;
 }
 
-impl<F, C, U> Func<C, ()> for F
-where
-    F: Fn(&mut C) -> U + 'static,
-{
-    type Output = U;
+/// Like `Func`, but the context is only borrowed shared
+pub trait SharedFunc<C, T> {
+    type Output;
 
-    fn call(&self, ctx: &mut C, _arg: ()) -> Self::Output {
-        (self)(ctx)
-    }
+    fn call(&self, ctx: &C, input: T) -> Self::Output;
 }
 
+// Implements `Func` for closures taking the context by `&mut`, and `SharedFunc` for those taking it by `&`
 macro_rules! funcs {
-    ($first: ident) => {
-        impl< F, C, U, $first> Func<C, ($first,)> for F
+    ($func: ident [$($mut: tt)?]) => {
+        impl<F, C, U> $func<C, ()> for F
         where
-            F: 'static,
-            F: Fn(&mut C, $first) -> U,
-            // for< U: 'a,
-            // for< C: 'a
+            F: Fn(&$($mut)? C) -> U + 'static,
         {
             type Output = U;
-            fn call(& self, ctx: & mut C, input: ($first,)) -> Self::Output {
-               (self)(ctx, input.0)
+
+            fn call(&self, ctx: &$($mut)? C, _input: ()) -> Self::Output {
+                (self)(ctx)
             }
         }
     };
-    ($first: ident $($rest: ident)*) => {
-        funcs!($($rest)*);
+    ($func: ident [$($mut: tt)?] $first: ident $($rest: ident)*) => {
+        funcs!($func [$($mut)?] $($rest)*);
 
-        impl< F, C, U, $first, $($rest),*> Func<C, ($first, $($rest),*)> for F
+        impl<F, C, U, $first, $($rest),*> $func<C, ($first, $($rest,)*)> for F
         where
-             F: Fn(&mut C, $first, $($rest),*) -> U + 'static,
-
-
+            F: Fn(&$($mut)? C, $first, $($rest),*) -> U + 'static,
         {
             type Output = U;
-            fn call(& self,ctx: & mut C, input: ($first, $($rest),*)) -> Self::Output {
+
+            fn call(&self, ctx: &$($mut)? C, input: ($first, $($rest,)*)) -> Self::Output {
                 #[allow(non_snake_case)]
-                let ($first, $($rest),*) = input;
-                (self)(ctx,$first, $($rest),*)
+                let ($first, $($rest,)*) = input;
+                (self)(ctx, $first, $($rest),*)
             }
         }
-
     };
 }
 
-funcs!(T1 T2 T3 T4 T5 T6 T7 T8);
+funcs!(Func [mut] T1 T2 T3 T4 T5 T6 T7 T8);
+funcs!(SharedFunc [] T1 T2 T3 T4 T5 T6 T7 T8);
 
 #[cfg(feature = "async")]
 mod async_impl {
@@ -66,63 +60,56 @@ mod async_impl {
         fn call<'a>(&'a self, ctx: &'a mut C, input: T) -> Self::Future<'a>;
     }
 
-    impl<F, C, U> AsyncFunc<C, ()> for F
-    where
-        F: 'static,
-        for<'a> F: Fn(&'a mut C) -> U,
-        U: Future,
-        for<'a> U: 'a,
-    {
-        type Output = U::Output;
-        type Future<'a> = U where  C: 'a;
+    /// Like `AsyncFunc`, but the context is only borrowed shared
+    pub trait AsyncSharedFunc<C, T> {
+        type Output;
+        type Future<'a>: Future<Output = Self::Output> + 'a
+        where
+            Self: 'a,
+            C: 'a;
 
-        fn call<'a>(&'a self, ctx: &'a mut C, _input: ()) -> Self::Future<'a> {
-            (self)(ctx)
-        }
+        fn call<'a>(&'a self, ctx: &'a C, input: T) -> Self::Future<'a>;
     }
 
-    macro_rules! funcs {
-        ($first: ident) => {
-            impl< F, C, U, $first> AsyncFunc<C, ($first,)> for F
+    macro_rules! async_funcs {
+        ($func: ident [$($mut: tt)?]) => {
+            impl<F, C, U> $func<C, ()> for F
             where
-                F: 'static,
-                F: Fn(&mut C, $first) -> U,
+                F: Fn(&$($mut)? C) -> U + 'static,
                 U: Future,
-                for<'a> U: 'a
-                // for< U: 'a,
-                // for< C: 'a
+                for<'a> U: 'a,
             {
                 type Output = U::Output;
                 type Future<'a> = U where C: 'a;
-                fn call<'a>(&'a  self, ctx: &'a mut C, input: ($first,)) -> Self::Future<'a> {
-                   (self)(ctx, input.0)
+
+                fn call<'a>(&'a self, ctx: &'a $($mut)? C, _input: ()) -> Self::Future<'a> {
+                    (self)(ctx)
                 }
             }
         };
-        ($first: ident $($rest: ident)*) => {
-            funcs!($($rest)*);
+        ($func: ident [$($mut: tt)?] $first: ident $($rest: ident)*) => {
+            async_funcs!($func [$($mut)?] $($rest)*);
 
-            impl< F, C, U, $first, $($rest),*> AsyncFunc<C, ($first, $($rest),*)> for F
+            impl<F, C, U, $first, $($rest),*> $func<C, ($first, $($rest,)*)> for F
             where
-                 F: Fn(&mut C, $first, $($rest),*) -> U + 'static,
-                 U: Future,
-                 for<'a> U: 'a
-
-
+                F: Fn(&$($mut)? C, $first, $($rest),*) -> U + 'static,
+                U: Future,
+                for<'a> U: 'a,
             {
                 type Output = U::Output;
-                type Future<'a> = U where C:'a;
-                fn call<'a>(&'a self,ctx: &'a mut C, input: ($first, $($rest),*)) -> Self::Future<'a> {
+                type Future<'a> = U where C: 'a;
+
+                fn call<'a>(&'a self, ctx: &'a $($mut)? C, input: ($first, $($rest,)*)) -> Self::Future<'a> {
                     #[allow(non_snake_case)]
-                    let ($first, $($rest),*) = input;
-                    (self)(ctx,$first, $($rest),*)
+                    let ($first, $($rest,)*) = input;
+                    (self)(ctx, $first, $($rest),*)
                 }
             }
-
         };
     }
 
-    funcs!(T1 T2 T3 T4 T5 T6 T7 T8);
+    async_funcs!(AsyncFunc [mut] T1 T2 T3 T4 T5 T6 T7 T8);
+    async_funcs!(AsyncSharedFunc [] T1 T2 T3 T4 T5 T6 T7 T8);
 }
 
 #[cfg(feature = "async")]
@@ -151,7 +138,10 @@ mod async_method_impl {
         for<'a> U: 'a,
     {
         type Output = U::Output;
-        type Future<'a> = U where  C: 'a, S:'a;
+        type Future<'a> = U
+        where
+            C: 'a,
+            S: 'a;
 
         fn call<'a>(&'a self, this: &'a mut S, ctx: &'a mut C, _input: ()) -> Self::Future<'a> {
             (self)(this, ctx)
@@ -279,3 +269,220 @@ pub use async_method_impl::*;
 
 // #[cfg(all(feature = "service", not(feature = "async")))]
 // pub use method_impl::*;
+
+/// Adapts a function without a context parameter, like `fn add(a: i64, b: i64) -> i64`,
+/// to `Func`, `SharedFunc` and their async counterparts for any context type
+#[derive(Debug, Clone, Copy)]
+pub struct NoContext<F> {
+    func: F,
+}
+
+impl<F> NoContext<F> {
+    pub fn new(func: F) -> NoContext<F> {
+        NoContext { func }
+    }
+
+    pub fn into_inner(self) -> F {
+        self.func
+    }
+}
+
+pub fn no_context<F>(func: F) -> NoContext<F> {
+    NoContext::new(func)
+}
+
+impl<F, C, U> Func<C, ()> for NoContext<F>
+where
+    F: Fn() -> U + 'static,
+{
+    type Output = U;
+
+    fn call(&self, _ctx: &mut C, _arg: ()) -> Self::Output {
+        (self.func)()
+    }
+}
+
+impl<F, C, U> SharedFunc<C, ()> for NoContext<F>
+where
+    F: Fn() -> U + 'static,
+{
+    type Output = U;
+
+    fn call(&self, _ctx: &C, _arg: ()) -> Self::Output {
+        (self.func)()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, C, U> AsyncFunc<C, ()> for NoContext<F>
+where
+    F: Fn() -> U + 'static,
+    U: futures_core::Future,
+    for<'a> U: 'a,
+{
+    type Output = U::Output;
+    type Future<'a> = U
+    where
+        C: 'a;
+
+    fn call<'a>(&'a self, _ctx: &'a mut C, _input: ()) -> Self::Future<'a> {
+        (self.func)()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, C, U> AsyncSharedFunc<C, ()> for NoContext<F>
+where
+    F: Fn() -> U + 'static,
+    U: futures_core::Future,
+    for<'a> U: 'a,
+{
+    type Output = U::Output;
+    type Future<'a> = U
+    where
+        C: 'a;
+
+    fn call<'a>(&'a self, _ctx: &'a C, _input: ()) -> Self::Future<'a> {
+        (self.func)()
+    }
+}
+
+macro_rules! no_context_funcs {
+    (@impl $($ty: ident)+) => {
+        impl<F, C, U, $($ty),+> Func<C, ($($ty,)+)> for NoContext<F>
+        where
+            F: Fn($($ty),+) -> U + 'static,
+        {
+            type Output = U;
+            fn call(&self, _ctx: &mut C, input: ($($ty,)+)) -> Self::Output {
+                #[allow(non_snake_case)]
+                let ($($ty,)+) = input;
+                (self.func)($($ty),+)
+            }
+        }
+
+        impl<F, C, U, $($ty),+> SharedFunc<C, ($($ty,)+)> for NoContext<F>
+        where
+            F: Fn($($ty),+) -> U + 'static,
+        {
+            type Output = U;
+            fn call(&self, _ctx: &C, input: ($($ty,)+)) -> Self::Output {
+                #[allow(non_snake_case)]
+                let ($($ty,)+) = input;
+                (self.func)($($ty),+)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<F, C, U, $($ty),+> AsyncFunc<C, ($($ty,)+)> for NoContext<F>
+        where
+            F: Fn($($ty),+) -> U + 'static,
+            U: futures_core::Future,
+            for<'a> U: 'a,
+        {
+            type Output = U::Output;
+            type Future<'a> = U where C: 'a;
+            fn call<'a>(&'a self, _ctx: &'a mut C, input: ($($ty,)+)) -> Self::Future<'a> {
+                #[allow(non_snake_case)]
+                let ($($ty,)+) = input;
+                (self.func)($($ty),+)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<F, C, U, $($ty),+> AsyncSharedFunc<C, ($($ty,)+)> for NoContext<F>
+        where
+            F: Fn($($ty),+) -> U + 'static,
+            U: futures_core::Future,
+            for<'a> U: 'a,
+        {
+            type Output = U::Output;
+            type Future<'a> = U where C: 'a;
+            fn call<'a>(&'a self, _ctx: &'a C, input: ($($ty,)+)) -> Self::Future<'a> {
+                #[allow(non_snake_case)]
+                let ($($ty,)+) = input;
+                (self.func)($($ty),+)
+            }
+        }
+    };
+    ($first: ident) => {
+        no_context_funcs!(@impl $first);
+    };
+    ($first: ident $($rest: ident)*) => {
+        no_context_funcs!($($rest)*);
+        no_context_funcs!(@impl $first $($rest)*);
+    };
+}
+
+no_context_funcs!(T1 T2 T3 T4 T5 T6 T7 T8);
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{
+        arguments::{Arguments, ToArguments},
+        testing::{Type, Value},
+        Callable, FuncExt,
+    };
+
+    /// A context no function here asks for
+    struct Unused;
+
+    fn answer() -> i64 {
+        42
+    }
+
+    #[test]
+    fn zero_argument_functions_ignore_the_context() {
+        let callable = no_context(answer).callable::<Value>();
+        assert_eq!(callable.signature().params().iter().count(), 0);
+
+        let ret = callable.call(&mut Unused, Arguments::default());
+        assert_eq!(ret.unwrap(), Value::Int(42));
+    }
+
+    #[test]
+    fn multi_argument_functions_ignore_the_context() {
+        let callable = no_context(|a: i64, b: i64, c: i64| a * b + c).callable::<Value>();
+        let signature = callable.signature();
+        let params = signature.params().iter().collect::<Vec<_>>();
+        assert_eq!(params, [&Type::Int, &Type::Int, &Type::Int]);
+
+        let ret = callable.call(&mut Unused, (6i64, 7i64, 0i64).to_arguments());
+        assert_eq!(ret.unwrap(), Value::Int(42));
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use futures::executor::block_on;
+
+        use super::*;
+        use crate::{AsyncCallable, AsyncSharedCallable, SharedFuncExt};
+
+        async fn double(n: i64) -> i64 {
+            n * 2
+        }
+
+        #[test]
+        fn async_functions_ignore_the_context() {
+            let callable = no_context(double).callable::<Value>();
+            assert_eq!(
+                AsyncCallable::signature(&callable).return_type(),
+                Some(&Type::Int)
+            );
+
+            let mut ctx = Unused;
+            let future = callable.call_async(&mut ctx, (21i64,).to_arguments());
+            assert_eq!(block_on(future).unwrap(), Value::Int(42));
+        }
+
+        #[test]
+        fn async_functions_take_a_shared_context() {
+            let callable = no_context(double).shared_callable::<Value>();
+            let future = callable.call_async(&Unused, (21i64,).to_arguments());
+            assert_eq!(block_on(future).unwrap(), Value::Int(42));
+        }
+    }
+}
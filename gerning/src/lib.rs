@@ -2,13 +2,30 @@
 
 extern crate alloc;
 
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 mod callable;
 #[cfg(feature = "async")]
 mod callable_async;
 mod callable_fn;
+#[cfg(feature = "async")]
+mod callable_stream;
+#[cfg(feature = "async")]
+mod cancel;
 mod error;
+#[cfg(feature = "async")]
+mod executor;
+mod extract;
 mod func;
+mod named;
 mod resultable;
+mod retry;
+mod shared;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "async")]
+mod time;
 mod traits;
 
 #[cfg(feature = "service")]
@@ -17,7 +34,15 @@ pub mod service;
 pub mod arguments;
 pub mod signature;
 
-pub use self::{callable::*, callable_fn::*, error::*, func::*, resultable::*, traits::*};
+pub use self::{
+    callable::*, callable_fn::*, error::*, extract::*, func::*, named::*, resultable::*, retry::*,
+    shared::*, traits::*,
+};
 
 #[cfg(feature = "async")]
-pub use self::callable_async::*;
+pub use self::{callable_async::*, callable_stream::*, cancel::*, executor::*, time::*};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
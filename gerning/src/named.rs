@@ -0,0 +1,165 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{arguments::Arguments, signature::Signature, Callable, Error, Frame, Value};
+
+#[cfg(feature = "async")]
+use crate::AsyncCallable;
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "async")]
+use futures_core::ready;
+#[cfg(feature = "async")]
+use pin_project_lite::pin_project;
+
+/// Names a callable, and optionally its parameters, in the errors it returns
+pub struct Named<T> {
+    callable: T,
+    name: String,
+    params: Vec<String>,
+}
+
+impl<T> Named<T> {
+    pub fn new(callable: T, name: impl Into<String>) -> Named<T> {
+        Named {
+            callable,
+            name: name.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Parameter names, in order. Used to name arguments that fail to extract
+    pub fn params<I, S>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.params = params.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn into_inner(self) -> T {
+        self.callable
+    }
+}
+
+fn annotate<V: Value>(error: Error<V>, name: &str, params: &[String]) -> Error<V> {
+    let error = match error {
+        Error::Argument(err) => Error::Argument(err.with_names(params)),
+        err => err,
+    };
+    error.context(Frame::Callable(name.into()))
+}
+
+impl<T, C, V> Callable<C, V> for Named<T>
+where
+    T: Callable<C, V>,
+    V: Value,
+{
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call(&self, ctx: &mut C, args: Arguments<V>) -> Result<V, Error<V>> {
+        self.callable
+            .call(ctx, args)
+            .map_err(|err| annotate(err, &self.name, &self.params))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, C, V> AsyncCallable<C, V> for Named<T>
+where
+    T: AsyncCallable<C, V>,
+    V: Value,
+{
+    type Future<'a>
+        = NamedFuture<'a, T::Future<'a>>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call_async<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Future<'a> {
+        NamedFuture {
+            future: self.callable.call_async(ctx, args),
+            name: &self.name,
+            params: &self.params,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    pub struct NamedFuture<'a, F> {
+        #[pin]
+        future: F,
+        name: &'a str,
+        params: &'a [String],
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, F, V> Future for NamedFuture<'a, F>
+where
+    F: Future<Output = Result<V, Error<V>>>,
+    V: Value,
+{
+    type Output = Result<V, Error<V>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let ret = ready!(this.future.poll(cx));
+        Poll::Ready(ret.map_err(|err| annotate(err, this.name, this.params)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{arguments::ToArguments, testing::Value, CallableExt, FuncExt};
+
+    fn add(_ctx: &mut (), a: i64, b: i64) -> i64 {
+        a + b
+    }
+
+    #[test]
+    fn errors_are_framed_with_the_name() {
+        let add = add.callable::<Value>().named("add").params(["a", "b"]);
+        assert_eq!(add.name(), "add");
+
+        let err = add.call(&mut (), (1i64, "two").to_arguments()).unwrap_err();
+        assert_eq!(
+            err.frames().collect::<Vec<_>>(),
+            [&Frame::Callable("add".into())]
+        );
+        assert!(matches!(
+            err.root(),
+            Error::Argument(crate::arguments::ArgumentError::Parameter { index: 1, name: Some(name), .. })
+                if name == "b"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "callable `add`: argument 1 (`b`): invalid type. Expected: Int, found: String"
+        );
+    }
+
+    #[test]
+    fn successful_calls_pass_through() {
+        let add = add.callable::<Value>().named("add");
+        let ret = add.call(&mut (), (1i64, 2i64).to_arguments()).unwrap();
+        assert_eq!(ret, Value::Int(3));
+    }
+}
@@ -0,0 +1,557 @@
+use alloc::sync::Arc;
+use core::{fmt, time::Duration};
+
+use crate::{arguments::Arguments, signature::Signature, Callable, Error, Value};
+
+#[cfg(feature = "async")]
+use crate::{time::Timer, AsyncCallable};
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "async")]
+use futures_core::ready;
+#[cfg(feature = "async")]
+use pin_project_lite::pin_project;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    None,
+    Fixed(Duration),
+    Exponential {
+        initial: Duration,
+        factor: u32,
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    /// The delay before retry number `attempt`, starting at 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => initial
+                .saturating_mul(factor.saturating_pow(attempt.saturating_sub(1)))
+                .min(max),
+        }
+    }
+}
+
+type Predicate<V> = Arc<dyn Fn(&Error<V>) -> bool + Send + Sync>;
+
+pub struct RetryPolicy<V: Value> {
+    max_attempts: u32,
+    backoff: Backoff,
+    predicate: Predicate<V>,
+}
+
+impl<V: Value> Clone for RetryPolicy<V> {
+    fn clone(&self) -> Self {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            backoff: self.backoff,
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+impl<V: Value> fmt::Debug for RetryPolicy<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<V: Value> RetryPolicy<V> {
+    /// Call at most `max_attempts` times.
+    /// By default every error but argument errors is retried, without delay
+    pub fn new(max_attempts: u32) -> RetryPolicy<V> {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::None,
+            predicate: Arc::new(|err| !matches!(err.root(), Error::Argument(_))),
+        }
+    }
+
+    /// Without the `std` feature the sync `Retry` can't wait, see there
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Error<V>) -> bool + Send + Sync + 'static,
+    {
+        self.predicate = Arc::new(predicate);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether to make attempt number `attempt + 1` after `error`
+    pub fn should_retry(&self, attempt: u32, error: &Error<V>) -> bool {
+        attempt < self.max_attempts && (self.predicate)(error)
+    }
+
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.delay(attempt)
+    }
+
+    /// The arguments for attempt number `attempt`.
+    /// They are only cloned if another attempt may follow, the last one takes them
+    fn attempt_args(&self, attempt: u32, args: &mut Option<Arguments<V>>) -> Arguments<V>
+    where
+        V: Clone,
+    {
+        let args = if attempt < self.max_attempts {
+            args.clone()
+        } else {
+            args.take()
+        };
+        args.expect("no attempt after the last one")
+    }
+}
+
+/// Calls the wrapped callable again on failure.
+///
+/// Waiting between attempts needs `std::thread::sleep`, so backoff delays are
+/// unsupported without the `std` feature and attempts follow each other right away.
+/// Use `AsyncRetry` with a `Timer` to wait without `std`
+pub struct Retry<F, V: Value> {
+    callable: F,
+    policy: RetryPolicy<V>,
+}
+
+impl<F, V: Value> Retry<F, V> {
+    pub fn new(callable: F, policy: RetryPolicy<V>) -> Retry<F, V> {
+        Retry { callable, policy }
+    }
+}
+
+impl<F: Clone, V: Value> Clone for Retry<F, V> {
+    fn clone(&self) -> Self {
+        Retry {
+            callable: self.callable.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<F, C, V> Callable<C, V> for Retry<F, V>
+where
+    F: Callable<C, V>,
+    V: Value + Clone,
+{
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call(&self, ctx: &mut C, args: Arguments<V>) -> Result<V, Error<V>> {
+        let mut args = Some(args);
+        let mut attempt = 1;
+        loop {
+            let args = self.policy.attempt_args(attempt, &mut args);
+            match self.callable.call(ctx, args) {
+                Err(err) if self.policy.should_retry(attempt, &err) => {
+                    #[cfg(feature = "std")]
+                    {
+                        let delay = self.policy.delay(attempt);
+                        if !delay.is_zero() {
+                            std::thread::sleep(delay);
+                        }
+                    }
+                    attempt += 1;
+                }
+                ret => return ret,
+            }
+        }
+    }
+}
+
+/// Calls the wrapped async callable again on failure, sleeping with `T` between attempts
+#[cfg(feature = "async")]
+pub struct AsyncRetry<F, T, V: Value> {
+    callable: F,
+    policy: RetryPolicy<V>,
+    _timer: PhantomData<T>,
+}
+
+#[cfg(feature = "async")]
+impl<F, T, V: Value> AsyncRetry<F, T, V> {
+    pub fn new(callable: F, policy: RetryPolicy<V>) -> AsyncRetry<F, T, V> {
+        AsyncRetry {
+            callable,
+            policy,
+            _timer: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, T, C, V> AsyncCallable<C, V> for AsyncRetry<F, T, V>
+where
+    F: AsyncCallable<C, V>,
+    T: Timer,
+    V: Value + Clone,
+{
+    type Future<'a>
+        = RetryFuture<'a, F, C, T, V>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call_async<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Future<'a> {
+        let ctx = ctx as *mut C;
+        let mut args = Some(args);
+        let future = self
+            .callable
+            .call_async(unsafe { &mut *ctx }, self.policy.attempt_args(1, &mut args));
+
+        RetryFuture {
+            callable: &self.callable,
+            policy: &self.policy,
+            ctx,
+            args,
+            attempt: 1,
+            state: RetryState::Call { future },
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    #[project = RetryProj]
+    enum RetryState<F, S> {
+        Call {
+            #[pin]
+            future: F,
+        },
+        Sleep {
+            #[pin]
+            sleep: S,
+        },
+        Done,
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    pub struct RetryFuture<'a, F, C, T, V>
+    where
+        F: AsyncCallable<C, V>,
+        F: 'a,
+        C: 'a,
+        T: Timer,
+        V: Value,
+    {
+        callable: &'a F,
+        policy: &'a RetryPolicy<V>,
+        ctx: *mut C,
+        args: Option<Arguments<V>>,
+        attempt: u32,
+        #[pin]
+        state: RetryState<F::Future<'a>, T::Sleep>,
+    }
+}
+
+#[cfg(feature = "async")]
+unsafe impl<'a, F, C, T, V> Send for RetryFuture<'a, F, C, T, V>
+where
+    F: AsyncCallable<C, V> + Sync,
+    F::Future<'a>: Send,
+    C: Send,
+    T: Timer,
+    T::Sleep: Send,
+    V: Value + Send,
+{
+}
+
+#[cfg(feature = "async")]
+impl<'a, F, C, T, V> Future for RetryFuture<'a, F, C, T, V>
+where
+    F: AsyncCallable<C, V>,
+    T: Timer,
+    V: Value + Clone,
+{
+    type Output = Result<V, Error<V>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let mut this = self.as_mut().project();
+            let callable: &'a F = this.callable;
+
+            let delay = match this.state.as_mut().project() {
+                RetryProj::Call { future } => match ready!(future.poll(cx)) {
+                    Err(err) if this.policy.should_retry(*this.attempt, &err) => {
+                        let delay = this.policy.delay(*this.attempt);
+                        *this.attempt += 1;
+                        Some(delay)
+                    }
+                    ret => {
+                        this.state.set(RetryState::Done);
+                        return Poll::Ready(ret);
+                    }
+                },
+                RetryProj::Sleep { sleep } => {
+                    ready!(sleep.poll(cx));
+                    None
+                }
+                RetryProj::Done => panic!("poll after done"),
+            };
+
+            // The previous future must be dropped before the context is borrowed again
+            this.state.set(RetryState::Done);
+
+            match delay {
+                Some(delay) if !delay.is_zero() => {
+                    this.state.set(RetryState::Sleep {
+                        sleep: T::sleep(delay),
+                    });
+                }
+                _ => {
+                    let ctx: &'a mut C = unsafe { &mut **this.ctx };
+                    let args = this.policy.attempt_args(*this.attempt, this.args);
+                    let future = callable.call_async(ctx, args);
+                    this.state.set(RetryState::Call { future });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{sync::Arc, vec};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::CallableExt;
+
+    /// Counts how often it is cloned
+    #[derive(Debug)]
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Tracked(self.0.clone())
+        }
+    }
+
+    impl Value for Tracked {
+        type Type = ();
+
+        fn get_type(&self) {}
+    }
+
+    /// Fails until it has been called `succeed_on` times
+    struct Flaky {
+        succeed_on: usize,
+    }
+
+    impl Callable<usize, Tracked> for Flaky {
+        fn signature(&self) -> Signature<Tracked> {
+            Signature::new(crate::signature::Parameters::new(), ())
+        }
+
+        fn call(
+            &self,
+            calls: &mut usize,
+            _args: Arguments<Tracked>,
+        ) -> Result<Tracked, Error<Tracked>> {
+            *calls += 1;
+            if *calls < self.succeed_on {
+                return Err(Error::new("flaky"));
+            }
+            Ok(Tracked(Arc::new(AtomicUsize::new(0))))
+        }
+    }
+
+    fn call(
+        succeed_on: usize,
+        max_attempts: u32,
+    ) -> (Result<Tracked, Error<Tracked>>, usize, usize) {
+        let clones = Arc::new(AtomicUsize::new(0));
+        let args = Arguments::new(vec![Tracked(clones.clone())]);
+        let mut calls = 0;
+        let ret = Flaky { succeed_on }
+            .retry(RetryPolicy::new(max_attempts))
+            .call(&mut calls, args);
+        (ret, calls, clones.load(Ordering::SeqCst))
+    }
+
+    #[test]
+    fn first_attempt_succeeds_without_retries() {
+        let (ret, calls, clones) = call(1, 1);
+        assert!(ret.is_ok());
+        assert_eq!((calls, clones), (1, 0));
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let (ret, calls, clones) = call(3, 5);
+        assert!(ret.is_ok());
+        assert_eq!((calls, clones), (3, 3));
+    }
+
+    #[test]
+    fn the_last_attempt_takes_the_arguments() {
+        let (ret, calls, clones) = call(usize::MAX, 3);
+        assert!(ret.is_err());
+        assert_eq!((calls, clones), (3, 2));
+    }
+
+    #[test]
+    fn argument_errors_are_not_retried() {
+        let policy = RetryPolicy::<Tracked>::new(3);
+        let err = Error::Argument(crate::arguments::ArgumentError::Missing { index: 0, arity: 0 });
+        assert!(!policy.should_retry(1, &err));
+        assert!(policy.should_retry(1, &Error::new("flaky")));
+        assert!(!policy.should_retry(3, &Error::new("flaky")));
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use alloc::{boxed::Box, vec::Vec};
+        use futures::executor::block_on;
+        use std::sync::Mutex;
+
+        use super::*;
+        use crate::AsyncCallableExt;
+
+        /// Pending for one poll, to make every attempt and sleep suspend
+        struct YieldNow(bool);
+
+        impl Future for YieldNow {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    return Poll::Ready(());
+                }
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        /// Fails until it has been called `succeed_on` times
+        struct AsyncFlaky {
+            succeed_on: usize,
+        }
+
+        impl AsyncCallable<usize, Tracked> for AsyncFlaky {
+            type Future<'a> =
+                Pin<Box<dyn Future<Output = Result<Tracked, Error<Tracked>>> + Send + 'a>>;
+
+            fn signature(&self) -> Signature<Tracked> {
+                Signature::new(crate::signature::Parameters::new(), ())
+            }
+
+            fn call_async<'a>(
+                &'a self,
+                calls: &'a mut usize,
+                mut args: Arguments<Tracked>,
+            ) -> Self::Future<'a> {
+                Box::pin(async move {
+                    YieldNow(false).await;
+                    *calls += 1;
+                    if *calls < self.succeed_on {
+                        return Err(Error::new("flaky"));
+                    }
+                    // Hand back the argument, to see which attempt got the original
+                    args.try_take::<Tracked>(0).map_err(Error::Argument)
+                })
+            }
+        }
+
+        /// Only the backoff test has delays, the others never sleep
+        static SLEEPS: Mutex<Vec<Duration>> = Mutex::new(Vec::new());
+
+        /// Records the delays it is asked to sleep
+        struct Recorded;
+
+        impl Timer for Recorded {
+            type Sleep = YieldNow;
+
+            fn sleep(duration: Duration) -> Self::Sleep {
+                SLEEPS.lock().unwrap().push(duration);
+                YieldNow(false)
+            }
+        }
+
+        fn call(
+            succeed_on: usize,
+            policy: RetryPolicy<Tracked>,
+        ) -> (Result<Tracked, Error<Tracked>>, usize, Arc<AtomicUsize>) {
+            let clones = Arc::new(AtomicUsize::new(0));
+            let args = Arguments::new(vec![Tracked(clones.clone())]);
+            let mut calls = 0;
+            let callable = AsyncFlaky { succeed_on }.retry::<Recorded>(policy);
+
+            let future = callable.call_async(&mut calls, args);
+            // `RetryFuture` is `Send` by an unsafe impl
+            fn assert_send<T: Send>(_: &T) {}
+            assert_send(&future);
+
+            let ret = block_on(future);
+            (ret, calls, clones)
+        }
+
+        #[test]
+        fn retries_until_success() {
+            let (ret, calls, clones) = call(3, RetryPolicy::new(5));
+            assert!(ret.is_ok());
+            assert_eq!((calls, clones.load(Ordering::SeqCst)), (3, 3));
+        }
+
+        #[test]
+        fn sleeps_with_the_timer_between_attempts() {
+            let backoff = Backoff::Exponential {
+                initial: Duration::from_millis(10),
+                factor: 2,
+                max: Duration::from_secs(1),
+            };
+            let (ret, calls, _) = call(4, RetryPolicy::new(5).backoff(backoff));
+            assert!(ret.is_ok());
+            assert_eq!(calls, 4);
+            assert_eq!(
+                *SLEEPS.lock().unwrap(),
+                [10, 20, 40].map(Duration::from_millis)
+            );
+        }
+
+        #[test]
+        fn the_last_attempt_takes_the_original_arguments() {
+            let (ret, calls, clones) = call(3, RetryPolicy::new(3));
+            assert_eq!((calls, clones.load(Ordering::SeqCst)), (3, 2));
+            // The value handed back is the one passed in, not a clone of it
+            assert!(Arc::ptr_eq(&ret.unwrap().0, &clones));
+        }
+
+        #[test]
+        fn errors_which_are_not_retried_stop_at_once() {
+            let policy = RetryPolicy::new(5).retry_if(|_| false);
+            let (ret, calls, clones) = call(3, policy);
+            assert!(matches!(ret, Err(Error::Runtime(_))));
+            assert_eq!((calls, clones.load(Ordering::SeqCst)), (1, 1));
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use alloc::{boxed::Box, string::String, sync::Arc, task::Wake, vec::Vec};
+use avagarden::sync::Mutex;
+use core::{
+    future::Future,
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use locket::{LockApi, LockApiWriteGuard};
+
+use super::AsyncService;
+use crate::{arguments::Arguments, Error, Value};
+
+/// The calls woken since the batch last looked, and the task running the batch
+#[derive(Default)]
+struct Woken {
+    calls: Vec<usize>,
+    waker: Option<Waker>,
+}
+
+type SharedWoken = Arc<Mutex<Woken>>;
+
+fn woken(woken: &SharedWoken) -> Option<impl LockApiWriteGuard<'_, Woken>> {
+    <SharedWoken as LockApi<Woken>>::write(woken).ok()
+}
+
+/// Wakes the batch with the call it was handed to marked as woken,
+/// so only that call is polled again
+struct CallWaker {
+    idx: usize,
+    woken: SharedWoken,
+}
+
+impl Wake for CallWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let Some(mut lock) = woken(&self.woken) else {
+            return;
+        };
+        let woken = lock.get_mut();
+        woken.calls.push(self.idx);
+        let waker = woken.waker.take();
+        drop(lock);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Many calls against one service, driven concurrently.
+/// Every call gets its own context, so calls never share `&mut C`
+pub struct Batch<'a, S, C, V: Value> {
+    service: &'a S,
+    calls: Vec<(String, Arguments<V>)>,
+    parallelism: usize,
+    _ctx: PhantomData<fn(&mut C)>,
+}
+
+impl<'a, S, C, V> Batch<'a, S, C, V>
+where
+    S: AsyncService<C, V>,
+    V: Value,
+{
+    pub fn new(service: &'a S) -> Batch<'a, S, C, V> {
+        Batch {
+            service,
+            calls: Vec::new(),
+            parallelism: usize::MAX,
+            _ctx: PhantomData,
+        }
+    }
+
+    pub fn call(mut self, name: impl Into<String>, args: Arguments<V>) -> Self {
+        self.calls.push((name.into(), args));
+        self
+    }
+
+    pub fn calls<I, N>(mut self, calls: I) -> Self
+    where
+        I: IntoIterator<Item = (N, Arguments<V>)>,
+        N: Into<String>,
+    {
+        self.calls
+            .extend(calls.into_iter().map(|(name, args)| (name.into(), args)));
+        self
+    }
+
+    /// Run at most `parallelism` calls at a time. Unbounded by default
+    pub fn parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Run every call with a context from `factory`.
+    /// Results are returned in the order the calls were added
+    pub async fn run<F>(self, mut factory: F) -> Vec<Result<V, Error<V>>>
+    where
+        F: FnMut() -> C,
+    {
+        let service = self.service;
+        let limit = self.parallelism;
+
+        let (names, args): (Vec<_>, Vec<_>) = self.calls.into_iter().unzip();
+        let mut contexts: Vec<C> = names.iter().map(|_| factory()).collect();
+
+        let mut queue = contexts.iter_mut().zip(names.iter()).zip(args).enumerate();
+
+        let mut results: Vec<Option<Result<V, Error<V>>>> = names.iter().map(|_| None).collect();
+        // In-flight calls by index, polled only when their waker fired
+        let mut running: Vec<Option<Pin<Box<S::Call<'_>>>>> = names.iter().map(|_| None).collect();
+        let mut in_flight = 0;
+
+        let shared = SharedWoken::default();
+        let wakers: Vec<Waker> = (0..names.len())
+            .map(|idx| {
+                Waker::from(Arc::new(CallWaker {
+                    idx,
+                    woken: shared.clone(),
+                }))
+            })
+            .collect();
+
+        core::future::poll_fn(|cx| loop {
+            while in_flight < limit {
+                let Some((idx, ((ctx, name), args))) = queue.next() else {
+                    break;
+                };
+                running[idx] = Some(Box::pin(service.call(ctx, name.as_str(), args)));
+                in_flight += 1;
+                // New calls are polled right away
+                wakers[idx].wake_by_ref();
+            }
+
+            if in_flight == 0 {
+                return Poll::Ready(());
+            }
+
+            // The waker is stored before the woken calls are taken,
+            // so a call waking up in between is not missed
+            let woken = match woken(&shared) {
+                Some(mut lock) => {
+                    let woken = lock.get_mut();
+                    woken.waker = Some(cx.waker().clone());
+                    mem::take(&mut woken.calls)
+                }
+                None => return Poll::Pending,
+            };
+
+            if woken.is_empty() {
+                return Poll::Pending;
+            }
+
+            for idx in woken {
+                // Calls may be woken again after they completed
+                let Some(future) = running[idx].as_mut() else {
+                    continue;
+                };
+                if let Poll::Ready(ret) =
+                    future.as_mut().poll(&mut Context::from_waker(&wakers[idx]))
+                {
+                    results[idx] = Some(ret);
+                    running[idx] = None;
+                    in_flight -= 1;
+                }
+            }
+        })
+        .await;
+
+        results
+            .into_iter()
+            .map(|ret| ret.expect("call completed"))
+            .collect()
+    }
+
+    /// Run every call with a clone of `ctx`
+    pub async fn run_shared(self, ctx: &C) -> Vec<Result<V, Error<V>>>
+    where
+        C: Clone,
+    {
+        self.run(|| ctx.clone()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, string::ToString};
+    use futures::{channel::oneshot, task::noop_waker_ref, FutureExt};
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{service::ServiceSignature, testing::Value};
+
+    /// Calls wait for their sender to fire, counting how often they are polled
+    #[derive(Default)]
+    struct Gated {
+        receivers: Mutex<BTreeMap<String, oneshot::Receiver<i64>>>,
+        polls: Arc<Mutex<BTreeMap<String, usize>>>,
+    }
+
+    impl Gated {
+        fn gate(&self, name: &str) -> oneshot::Sender<i64> {
+            let (tx, rx) = oneshot::channel();
+            self.receivers.lock().unwrap().insert(name.to_string(), rx);
+            tx
+        }
+
+        fn polls(&self, name: &str) -> usize {
+            self.polls.lock().unwrap()[name]
+        }
+    }
+
+    impl AsyncService<(), Value> for Gated {
+        type Call<'a> = Pin<Box<dyn Future<Output = Result<Value, Error<Value>>> + 'a>>;
+
+        fn signature(&self) -> ServiceSignature<Value> {
+            ServiceSignature::default()
+        }
+
+        fn call<'a>(
+            &'a self,
+            _ctx: &'a mut (),
+            name: &'a str,
+            _args: Arguments<Value>,
+        ) -> Self::Call<'a> {
+            let mut rx = self.receivers.lock().unwrap().remove(name).unwrap();
+            let polls = self.polls.clone();
+            Box::pin(core::future::poll_fn(move |cx| {
+                *polls.lock().unwrap().entry(name.to_string()).or_default() += 1;
+                rx.poll_unpin(cx)
+                    .map(|ret| Ok(Value::Int(ret.map_err(Error::new)?)))
+            }))
+        }
+    }
+
+    fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        future.poll_unpin(&mut Context::from_waker(noop_waker_ref()))
+    }
+
+    #[test]
+    fn only_woken_calls_are_polled() {
+        let service = Gated::default();
+        let (a, b, c) = (service.gate("a"), service.gate("b"), service.gate("c"));
+
+        let batch = Batch::new(&service)
+            .call("a", Arguments::default())
+            .call("b", Arguments::default())
+            .call("c", Arguments::default());
+        let mut run = Box::pin(batch.run(|| ()));
+
+        assert!(poll(&mut run).is_pending());
+        a.send(1).unwrap();
+        assert!(poll(&mut run).is_pending());
+        assert_eq!(
+            (service.polls("a"), service.polls("b"), service.polls("c")),
+            (2, 1, 1)
+        );
+
+        c.send(3).unwrap();
+        b.send(2).unwrap();
+        let Poll::Ready(results) = poll(&mut run) else {
+            panic!("batch should be done");
+        };
+        let results = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(results, [Value::Int(1), Value::Int(2), Value::Int(3)]);
+        assert_eq!((service.polls("b"), service.polls("c")), (2, 2));
+    }
+
+    #[test]
+    fn parallelism_bounds_the_calls_in_flight() {
+        let service = Gated::default();
+        let (a, b) = (service.gate("a"), service.gate("b"));
+
+        let batch = Batch::new(&service)
+            .call("a", Arguments::default())
+            .call("b", Arguments::default())
+            .parallelism(1);
+        let mut run = Box::pin(batch.run(|| ()));
+
+        assert!(poll(&mut run).is_pending());
+        // `b` only starts once `a` is done
+        assert!(service.polls.lock().unwrap().get("b").is_none());
+        b.send(2).unwrap();
+        a.send(1).unwrap();
+
+        let Poll::Ready(results) = poll(&mut run) else {
+            panic!("batch should be done");
+        };
+        let results = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(results, [Value::Int(1), Value::Int(2)]);
+    }
+}
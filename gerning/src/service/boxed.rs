@@ -3,7 +3,11 @@ mod r#async {
     use alloc::boxed::Box;
     use futures_core::future::BoxFuture;
 
-    use crate::{arguments::Arguments, service::ServiceSignature, signature, Error, Value};
+    use crate::{
+        arguments::Arguments,
+        service::{MethodId, ServiceSignature},
+        Error, Value,
+    };
 
     use super::super::AsyncService;
 
@@ -17,6 +21,15 @@ mod r#async {
             name: &'a str,
             args: Arguments<V>,
         ) -> BoxFuture<'a, Result<V, Error<V>>>;
+
+        fn resolve(&self, name: &str) -> Option<MethodId>;
+
+        fn call_by_id<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            id: &'a MethodId,
+            args: Arguments<V>,
+        ) -> BoxFuture<'a, Result<V, Error<V>>>;
     }
 
     impl<C, V: Value> DynamicAsyncService<C, V> for Box<dyn DynamicAsyncService<C, V> + Send + Sync> {
@@ -40,6 +53,19 @@ mod r#async {
         ) -> BoxFuture<'a, Result<V, Error<V>>> {
             (**self).call(ctx, name, args)
         }
+
+        fn resolve(&self, name: &str) -> Option<MethodId> {
+            (**self).resolve(name)
+        }
+
+        fn call_by_id<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            id: &'a MethodId,
+            args: Arguments<V>,
+        ) -> BoxFuture<'a, Result<V, Error<V>>> {
+            (**self).call_by_id(ctx, id, args)
+        }
     }
 
     pub struct BoxedDynamicAsyncService<T>(T);
@@ -71,6 +97,19 @@ mod r#async {
         ) -> BoxFuture<'a, Result<V, Error<V>>> {
             Box::pin(self.0.call(ctx, name, args))
         }
+
+        fn resolve(&self, name: &str) -> Option<MethodId> {
+            self.0.resolve(name)
+        }
+
+        fn call_by_id<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            id: &'a MethodId,
+            args: Arguments<V>,
+        ) -> BoxFuture<'a, Result<V, Error<V>>> {
+            Box::pin(self.0.call_by_id(ctx, id, args))
+        }
     }
 
     impl<C: 'static, V: Value + 'static> AsyncService<C, V> for BoxAsyncService<C, V> {
@@ -95,9 +134,22 @@ mod r#async {
         fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
             <Self as DynamicAsyncService<C, V>>::call(self, ctx, name, args)
         }
+
+        fn resolve(&self, name: &str) -> Option<MethodId> {
+            <Self as DynamicAsyncService<C, V>>::resolve(self, name)
+        }
+
+        fn call_by_id<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            id: &'a MethodId,
+            args: Arguments<V>,
+        ) -> Self::Call<'a> {
+            <Self as DynamicAsyncService<C, V>>::call_by_id(self, ctx, id, args)
+        }
     }
 
-    pub fn box_service<C, V: Value, T>(service: T) -> BoxAsyncService<C, V>
+    pub fn box_service<C, V, T>(service: T) -> BoxAsyncService<C, V>
     where
         C: 'static,
         V: Value + 'static,
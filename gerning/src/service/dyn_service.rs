@@ -1,34 +1,36 @@
-use alloc::{
-    boxed::Box,
-    collections::BTreeMap,
-    string::{String, ToString},
-};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+#[cfg(feature = "async")]
+use alloc::string::{String, ToString};
 
 #[cfg(feature = "async")]
 use futures_core::{ready, Future};
-use hashbrown::HashMap;
 use locket::LockApiWriteGuard;
 #[cfg(feature = "async")]
 use pin_project_lite::pin_project;
 
 use super::{
     method::MethodCallable,
-    service::Service,
+    service::{MethodId, Service, ServiceSignature},
+    shared::{SharedMethodCallable, SharedService},
     state::{HasState, StateType, SyncState},
-    State,
+    transaction::Transaction,
 };
 #[cfg(feature = "async")]
 use super::{
     method::{AsyncMethodCallable, BoxAsyncMethodCallable},
     service::AsyncService,
+    shared::AsyncSharedService,
     state::AsyncStateType,
-    LocalBoxAsyncMethodCallable,
+    stream::{BoxStreamMethodCallable, LocalBoxStreamMethodCallable, StreamMethodCallable},
+    LocalBoxAsyncMethodCallable, State,
 };
 
-use crate::{arguments::Arguments, signature::Signature, Error, Value};
+use crate::{arguments::Arguments, signature::Signature, Error, ErrorContext, Frame, Value};
 pub trait ServiceType {
     type Callable<S, C, V>;
     type State<T>;
+    #[cfg(feature = "async")]
+    type Stream<S, C, V>;
 }
 
 pub struct Sync;
@@ -36,6 +38,18 @@ pub struct Sync;
 impl ServiceType for Sync {
     type Callable<S, C, V> = Box<dyn MethodCallable<S, C, V>>;
     type State<T> = SyncState<T>;
+    #[cfg(feature = "async")]
+    type Stream<S, C, V> = LocalBoxStreamMethodCallable<'static, S, C, V>;
+}
+
+/// Methods borrow the context shared, see `SharedService`
+pub struct SharedContext;
+
+impl ServiceType for SharedContext {
+    type Callable<S, C, V> = Box<dyn SharedMethodCallable<S, C, V> + Send + core::marker::Sync>;
+    type State<T> = SyncState<T>;
+    #[cfg(feature = "async")]
+    type Stream<S, C, V> = LocalBoxStreamMethodCallable<'static, S, C, V>;
 }
 
 #[cfg(feature = "async")]
@@ -45,6 +59,7 @@ pub struct Async;
 impl ServiceType for Async {
     type Callable<S, C, V> = LocalBoxAsyncMethodCallable<'static, S, C, V>;
     type State<T> = SyncState<T>;
+    type Stream<S, C, V> = LocalBoxStreamMethodCallable<'static, S, C, V>;
 }
 
 #[cfg(feature = "async")]
@@ -54,22 +69,126 @@ pub struct SendAsync;
 impl ServiceType for SendAsync {
     type Callable<S, C, V> = BoxAsyncMethodCallable<'static, S, C, V>;
     type State<T> = SyncState<T>;
+    type Stream<S, C, V> = BoxStreamMethodCallable<'static, S, C, V>;
+}
+
+/// A registered method. `generation` counts how often it was registered,
+/// to tell stale `MethodId`s apart
+struct MethodSlot<M> {
+    name: Arc<str>,
+    generation: u32,
+    method: M,
+}
+
+/// The slot holding a method of a `DynService` over the state `T`
+type Slot<T, S, C, V> = MethodSlot<<S as ServiceType>::Callable<<T as HasState>::State, C, V>>;
+
+/// `MethodNotFound`, in the frame of the method that was asked for
+fn not_found<V: Value>(name: &str) -> Error<V> {
+    Error::MethodNotFound.context(Frame::Method(name.into()))
 }
 
+/// Method errors, and `MethodNotFound`, are wrapped in an `Error::Context` frame naming the method,
+/// so callers see `Context` as the top-level variant. Match on `Error::root` for the cause
 pub struct DynService<T: HasState, S: ServiceType, C, V: Value> {
     state: T,
-    methods: BTreeMap<String, S::Callable<T::State, C, V>>,
+    methods: Vec<Slot<T, S, C, V>>,
+    /// Index into `methods`, slots are never removed
+    names: BTreeMap<Arc<str>, usize>,
+    #[cfg(feature = "async")]
+    streams: BTreeMap<String, S::Stream<T::State, C, V>>,
+    /// Signatures of methods and streams, taken once at registration
+    signature: ServiceSignature<V>,
 }
 
-// impl<T, S, C, V: Value> DynService<T, S, C, V>
-// where
-//     S: ServiceType,
-//     T: HasState,
-// {
-//     fn method(&self, name: &str) -> Option<&S::Callable<T::State, C, V>> {
-//         self.methods.get(name)
-//     }
-// }
+impl<T, S, C, V: Value> DynService<T, S, C, V>
+where
+    S: ServiceType,
+    T: HasState,
+{
+    /// A handle to the method `name`, valid until the method is registered again.
+    /// Streams have no handles, they are looked up by name once, when opened
+    pub fn resolve(&self, name: &str) -> Option<MethodId> {
+        let index = *self.names.get(name)?;
+        let slot = &self.methods[index];
+        Some(MethodId::new(
+            index as u32,
+            slot.generation,
+            slot.name.clone(),
+        ))
+    }
+
+    fn method(&self, name: &str) -> Option<&Slot<T, S, C, V>> {
+        self.names.get(name).map(|&index| &self.methods[index])
+    }
+
+    fn method_by_id(&self, id: &MethodId) -> Result<&Slot<T, S, C, V>, Error<V>> {
+        let Some(slot) = self.methods.get(id.index() as usize) else {
+            return Err(not_found(id.name()));
+        };
+
+        if slot.generation != id.generation() || *slot.name != *id.name() {
+            return Err(Error::StaleMethod);
+        }
+
+        Ok(slot)
+    }
+
+    /// Methods and streams share the signature entries, so a name is either one or the other
+    #[cfg(feature = "async")]
+    fn check_name(&self, name: &str, stream: bool) -> Result<(), Error<V>> {
+        let taken = if stream {
+            self.names.contains_key(name)
+        } else {
+            self.streams.contains_key(name)
+        };
+        if taken {
+            Err(Error::NameTaken(name.into()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Registering a name again replaces the method in its slot,
+    /// and invalidates the handles to it.
+    fn insert_method(
+        &mut self,
+        name: &str,
+        signature: Signature<V>,
+        method: S::Callable<T::State, C, V>,
+    ) {
+        self.signature.insert(name, signature);
+
+        match self.names.get(name) {
+            Some(&index) => {
+                let slot = &mut self.methods[index];
+                slot.generation = slot.generation.wrapping_add(1);
+                slot.method = method;
+            }
+            None => {
+                let name: Arc<str> = name.into();
+                self.names.insert(name.clone(), self.methods.len());
+                self.methods.push(MethodSlot {
+                    name,
+                    generation: 0,
+                    method,
+                });
+            }
+        }
+    }
+
+    /// Registering a name again replaces the stream
+    #[cfg(feature = "async")]
+    fn insert_stream(
+        &mut self,
+        name: &str,
+        signature: Signature<V>,
+        method: S::Stream<T::State, C, V>,
+    ) {
+        self.signature.insert(name, signature);
+        self.streams.insert(name.to_string(), method);
+    }
+}
 
 impl<T, C, V: Value> DynService<T, Sync, C, V>
 where
@@ -79,8 +198,36 @@ where
         DynService {
             state,
             methods: Default::default(),
+            names: Default::default(),
+            #[cfg(feature = "async")]
+            streams: Default::default(),
+            signature: Default::default(),
+        }
+    }
+}
+
+impl<T, C, V: Value> DynService<T, SharedContext, C, V>
+where
+    T: HasState,
+{
+    pub fn new_shared(state: T) -> DynService<T, SharedContext, C, V> {
+        DynService {
+            state,
+            methods: Default::default(),
+            names: Default::default(),
+            #[cfg(feature = "async")]
+            streams: Default::default(),
+            signature: Default::default(),
         }
     }
+
+    pub fn register<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: SharedMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+    {
+        self.insert_method(name, method.signature(), Box::new(method));
+        self
+    }
 }
 
 #[cfg(feature = "async")]
@@ -92,6 +239,9 @@ where
         DynService {
             state,
             methods: Default::default(),
+            names: Default::default(),
+            streams: Default::default(),
+            signature: Default::default(),
         }
     }
 
@@ -99,6 +249,9 @@ where
         DynService {
             state,
             methods: Default::default(),
+            names: Default::default(),
+            streams: Default::default(),
+            signature: Default::default(),
         }
     }
 }
@@ -108,12 +261,59 @@ where
     T: HasState,
     V: Value,
 {
+    /// Panics if `name` is registered as a stream, see `try_register`
     pub fn register<U>(&mut self, name: &str, method: U) -> &mut Self
     where
         U: MethodCallable<T::State, C, V> + 'static,
     {
-        self.methods.insert(name.to_string(), Box::new(method));
-        self
+        self.try_register(name, method)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with `Error::NameTaken` if `name` is registered as a stream
+    pub fn try_register<U>(&mut self, name: &str, method: U) -> Result<&mut Self, Error<V>>
+    where
+        U: MethodCallable<T::State, C, V> + 'static,
+    {
+        #[cfg(feature = "async")]
+        self.check_name(name, false)?;
+        self.insert_method(name, method.signature(), Box::new(method));
+        Ok(self)
+    }
+
+    pub fn register_transactional<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: MethodCallable<T::State, C, V> + 'static,
+        T::State: Clone,
+    {
+        self.register(name, Transaction::new(method))
+    }
+
+    /// Panics if `name` is registered as a method, see `try_register_stream`
+    #[cfg(feature = "async")]
+    pub fn register_stream<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: StreamMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: 'static,
+        V: 'static,
+        C: 'static,
+    {
+        self.try_register_stream(name, method)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with `Error::NameTaken` if `name` is registered as a method
+    #[cfg(feature = "async")]
+    pub fn try_register_stream<U>(&mut self, name: &str, method: U) -> Result<&mut Self, Error<V>>
+    where
+        U: StreamMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: 'static,
+        V: 'static,
+        C: 'static,
+    {
+        self.check_name(name, true)?;
+        self.insert_stream(name, method.signature(), Box::new(method));
+        Ok(self)
     }
 }
 
@@ -123,13 +323,57 @@ where
     T: HasState,
     V: Value + 'static,
 {
+    /// Panics if `name` is registered as a stream, see `try_register`
     pub fn register<U>(&mut self, name: &str, method: U) -> &mut Self
     where
         U: AsyncMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
         for<'a> C: 'a,
     {
-        self.methods.insert(name.to_string(), Box::new(method));
-        self
+        self.try_register(name, method)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with `Error::NameTaken` if `name` is registered as a stream
+    pub fn try_register<U>(&mut self, name: &str, method: U) -> Result<&mut Self, Error<V>>
+    where
+        U: AsyncMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        for<'a> C: 'a,
+    {
+        self.check_name(name, false)?;
+        self.insert_method(name, method.signature(), Box::new(method));
+        Ok(self)
+    }
+
+    pub fn register_transactional<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: AsyncMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: Clone,
+        for<'a> C: 'a,
+    {
+        self.register(name, Transaction::new(method))
+    }
+
+    /// Panics if `name` is registered as a method, see `try_register_stream`
+    pub fn register_stream<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: StreamMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: 'static,
+        C: 'static,
+    {
+        self.try_register_stream(name, method)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with `Error::NameTaken` if `name` is registered as a method
+    pub fn try_register_stream<U>(&mut self, name: &str, method: U) -> Result<&mut Self, Error<V>>
+    where
+        U: StreamMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: 'static,
+        C: 'static,
+    {
+        self.check_name(name, true)?;
+        self.insert_stream(name, method.signature(), Box::new(method));
+        Ok(self)
     }
 }
 
@@ -139,14 +383,116 @@ where
     T: HasState,
     V: Value + 'static,
 {
+    /// Panics if `name` is registered as a stream, see `try_register`
     pub fn register<U>(&mut self, name: &str, method: U) -> &mut Self
     where
         U: AsyncMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
         for<'a> U::Future<'a>: Send,
         for<'a> C: 'a,
     {
-        self.methods.insert(name.to_string(), Box::new(method));
-        self
+        self.try_register(name, method)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with `Error::NameTaken` if `name` is registered as a stream
+    pub fn try_register<U>(&mut self, name: &str, method: U) -> Result<&mut Self, Error<V>>
+    where
+        U: AsyncMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        for<'a> U::Future<'a>: Send,
+        for<'a> C: 'a,
+    {
+        self.check_name(name, false)?;
+        self.insert_method(name, method.signature(), Box::new(method));
+        Ok(self)
+    }
+
+    pub fn register_transactional<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: AsyncMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        for<'a> U::Future<'a>: Send,
+        T::State: Clone + Send + 'static,
+        for<'a> C: 'a,
+    {
+        self.register(name, Transaction::new(method))
+    }
+
+    /// Panics if `name` is registered as a method, see `try_register_stream`
+    pub fn register_stream<U>(&mut self, name: &str, method: U) -> &mut Self
+    where
+        U: StreamMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: 'static,
+        for<'a> U::Stream<'a>: Send,
+        C: 'static,
+    {
+        self.try_register_stream(name, method)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fails with `Error::NameTaken` if `name` is registered as a method
+    pub fn try_register_stream<U>(&mut self, name: &str, method: U) -> Result<&mut Self, Error<V>>
+    where
+        U: StreamMethodCallable<T::State, C, V> + 'static + Send + core::marker::Sync,
+        T::State: 'static,
+        for<'a> U::Stream<'a>: Send,
+        C: 'static,
+    {
+        self.check_name(name, true)?;
+        self.insert_stream(name, method.signature(), Box::new(method));
+        Ok(self)
+    }
+}
+
+/// The stream opened by a stream method of a `DynService` over the state `T`
+#[cfg(feature = "async")]
+type MethodStream<'a, T, S, C, V> =
+    <<S as ServiceType>::Stream<<T as HasState>::State, C, V> as StreamMethodCallable<
+        <T as HasState>::State,
+        C,
+        V,
+    >>::Stream<'a>;
+
+#[cfg(feature = "async")]
+impl<T, S, C, V> DynService<T, S, C, V>
+where
+    T: HasState,
+    S: ServiceType,
+    S::Stream<T::State, C, V>: StreamMethodCallable<T::State, C, V>,
+    V: Value,
+{
+    /// Open the stream `name`, holding the state lock only while it is opened
+    pub fn call_stream<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        name: &str,
+        args: Arguments<V>,
+    ) -> Result<MethodStream<'a, T, S, C, V>, Error<V>>
+    where
+        T: StateType<V>,
+    {
+        let Some(method) = self.streams.get(name) else {
+            return Err(not_found(name));
+        };
+
+        let mut lock = self.state.get()?;
+        Ok(method.call_stream(lock.get_mut(), ctx, args))
+    }
+
+    /// Like `call_stream`, for states behind an async lock
+    pub async fn call_stream_async<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        name: &'a str,
+        args: Arguments<V>,
+    ) -> Result<MethodStream<'a, T, S, C, V>, Error<V>>
+    where
+        T: AsyncStateType<V>,
+    {
+        let Some(method) = self.streams.get(name) else {
+            return Err(not_found(name));
+        };
+
+        let mut lock = self.state.get().await?;
+        Ok(method.call_stream(lock.get_mut(), ctx, args))
     }
 }
 
@@ -166,14 +512,8 @@ where
     // type Set<'a> = SetFuture<'a, T, V>;
     type Call<'a> = AsyncMethodCallFuture<'a, S, T, C, V>;
 
-    fn signature(&self) -> super::ServiceSignature<V> {
-        let mut map: HashMap<String, Signature<V>> = HashMap::default();
-
-        for (name, call) in &self.methods {
-            map.insert(name.clone(), call.signature());
-        }
-
-        map.into()
+    fn signature(&self) -> ServiceSignature<V> {
+        self.signature.clone()
     }
 
     // fn set_value<'a>(&'a self, name: &'a str, value: V) -> Self::Set<'a> {
@@ -191,14 +531,25 @@ where
     // }
 
     fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
-        AsyncMethodCallFuture {
-            state: AsyncMethodCallFutureState::Init {
-                methods: &self.methods,
-                state: self.state.get(),
-                name,
-                ctx: Some(ctx),
-                args: Some(args),
-            },
+        match self.method(name) {
+            Some(slot) => AsyncMethodCallFuture::new(self, slot, ctx, args),
+            None => AsyncMethodCallFuture::error(not_found(name)),
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        DynService::resolve(self, name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        match self.method_by_id(id) {
+            Ok(slot) => AsyncMethodCallFuture::new(self, slot, ctx, args),
+            Err(err) => AsyncMethodCallFuture::error(err),
         }
     }
 }
@@ -211,14 +562,8 @@ where
     // T::State: State<V>,
     V: Value,
 {
-    fn signature(&self) -> super::ServiceSignature<V> {
-        let mut map: HashMap<String, Signature<V>> = HashMap::default();
-
-        for (name, call) in &self.methods {
-            map.insert(name.clone(), call.signature());
-        }
-
-        map.into()
+    fn signature(&self) -> ServiceSignature<V> {
+        self.signature.clone()
     }
 
     // fn set_value(&self, name: &str, value: V) -> Result<(), Error<V>> {
@@ -232,12 +577,169 @@ where
     // }
 
     fn call(&self, ctx: &mut C, name: &str, args: Arguments<V>) -> Result<V, Error<V>> {
-        let Some(method) = self.methods.get(name) else {
-            return Err(Error::MethodNotFound);
+        let Some(slot) = self.method(name) else {
+            return Err(not_found(name));
+        };
+
+        let mut lock = self.state.get()?;
+        slot.method
+            .call(lock.get_mut(), ctx, args)
+            .context(Frame::Method(name.into()))
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        DynService::resolve(self, name)
+    }
+
+    fn call_by_id(&self, ctx: &mut C, id: &MethodId, args: Arguments<V>) -> Result<V, Error<V>> {
+        let slot = self.method_by_id(id)?;
+
+        let mut lock = self.state.get()?;
+        slot.method
+            .call(lock.get_mut(), ctx, args)
+            .context(Frame::Method(id.name().into()))
+    }
+}
+
+impl<T, S, C, V> SharedService<C, V> for DynService<T, S, C, V>
+where
+    S: ServiceType + 'static,
+    S::Callable<T::State, C, V>: SharedMethodCallable<T::State, C, V>,
+    T: StateType<V>,
+    V: Value,
+{
+    fn signature(&self) -> ServiceSignature<V> {
+        self.signature.clone()
+    }
+
+    fn call(&self, ctx: &C, name: &str, args: Arguments<V>) -> Result<V, Error<V>> {
+        let Some(slot) = self.method(name) else {
+            return Err(not_found(name));
         };
 
         let mut lock = self.state.get()?;
-        method.call(lock.get_mut(), ctx, args)
+        slot.method
+            .call(lock.get_mut(), ctx, args)
+            .context(Frame::Method(name.into()))
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        DynService::resolve(self, name)
+    }
+
+    fn call_by_id(&self, ctx: &C, id: &MethodId, args: Arguments<V>) -> Result<V, Error<V>> {
+        let slot = self.method_by_id(id)?;
+
+        let mut lock = self.state.get()?;
+        slot.method
+            .call(lock.get_mut(), ctx, args)
+            .context(Frame::Method(id.name().into()))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S, T, C, V> AsyncSharedService<C, V> for DynService<T, S, C, V>
+where
+    S: ServiceType + 'static,
+    S::Callable<T::State, C, V>: SharedMethodCallable<T::State, C, V>,
+    T: AsyncStateType<V>,
+    V: Value,
+    for<'a> T: 'a,
+    for<'a> C: 'a,
+{
+    type Call<'a> = SharedMethodCallFuture<'a, S, T, C, V>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> ServiceSignature<V> {
+        self.signature.clone()
+    }
+
+    fn call<'a>(&'a self, ctx: &'a C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
+        SharedMethodCallFuture {
+            state: self.state.get(),
+            method: match self.method(name) {
+                Some(slot) => Ok(&slot.method),
+                None => Err(Some(not_found(name))),
+            },
+            name,
+            ctx,
+            args: Some(args),
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        DynService::resolve(self, name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        SharedMethodCallFuture {
+            state: self.state.get(),
+            method: self.method_by_id(id).map(|slot| &slot.method).map_err(Some),
+            name: id.name(),
+            ctx,
+            args: Some(args),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    pub struct SharedMethodCallFuture<'a, S: ServiceType, T, C, V: Value>
+    where
+        T: AsyncStateType<V>,
+        T: 'a,
+        S::Callable<T::State, C, V>: SharedMethodCallable<T::State, C, V>,
+        T::State: 'a,
+    {
+        #[pin]
+        state: T::Future<'a>,
+        // The error is taken when the future is polled
+        method: Result<&'a S::Callable<T::State, C, V>, Option<Error<V>>>,
+        name: &'a str,
+        ctx: &'a C,
+        args: Option<Arguments<V>>,
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, S: ServiceType, T: AsyncStateType<V>, C, V: Value> core::future::Future
+    for SharedMethodCallFuture<'a, S, T, C, V>
+where
+    S::Callable<T::State, C, V>: SharedMethodCallable<T::State, C, V>,
+{
+    type Output = Result<V, Error<V>>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        let this = self.project();
+
+        let method = match this.method {
+            Ok(method) => *method,
+            Err(err) => {
+                return core::task::Poll::Ready(Err(err.take().expect("poll after done")));
+            }
+        };
+
+        let mut state = match ready!(this.state.poll(cx)) {
+            Ok(ret) => ret,
+            Err(err) => return core::task::Poll::Ready(Err(err.into())),
+        };
+
+        let args = this.args.take().expect("poll after done");
+        core::task::Poll::Ready(
+            method
+                .call(state.get_mut(), this.ctx, args)
+                .context(Frame::Method((*this.name).into())),
+        )
     }
 }
 
@@ -252,9 +754,11 @@ pin_project! {
         T: 'a
     {
         Init {
-            methods: &'a BTreeMap<String, S::Callable<T::State, C, V>>,
+            method: &'a S::Callable<T::State, C, V>,
+            source: &'a T,
+            // The lock is only taken on the first poll
             #[pin]
-            state: T::Future<'a>,
+            state: Option<T::Future<'a>>,
             ctx: Option<&'a mut C>,
             name: &'a str,
             args: Option<Arguments<V>>
@@ -263,6 +767,10 @@ pin_project! {
             state: T::Ref<'a>,
             #[pin]
             future: <S::Callable<T::State, C, V> as AsyncMethodCallable<T::State, C, V>>::Future<'a>,
+            name: &'a str,
+        },
+        Failed {
+            error: Option<Error<V>>,
         },
         Done,
     }
@@ -270,8 +778,11 @@ pin_project! {
 
 #[cfg(feature = "async")]
 pin_project! {
-    pub struct AsyncMethodCallFuture<'a, S: ServiceType, T: AsyncStateType<V>, C, V: Value>
+    /// Takes the state lock on the first poll, not when created,
+    /// and holds it until the method completes
+    pub struct AsyncMethodCallFuture<'a, S: ServiceType, T: AsyncStateType<V>, C, V>
     where
+        V: Value,
         V: 'static,
         S::Callable<T::State, C, V>: AsyncMethodCallable<T::State, C, V>
     {
@@ -280,6 +791,36 @@ pin_project! {
     }
 }
 
+#[cfg(feature = "async")]
+impl<'a, S: ServiceType, T: AsyncStateType<V>, C, V: Value> AsyncMethodCallFuture<'a, S, T, C, V>
+where
+    S::Callable<T::State, C, V>: AsyncMethodCallable<T::State, C, V>,
+{
+    fn new(
+        service: &'a DynService<T, S, C, V>,
+        slot: &'a Slot<T, S, C, V>,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> Self {
+        AsyncMethodCallFuture {
+            state: AsyncMethodCallFutureState::Init {
+                method: &slot.method,
+                source: &service.state,
+                state: None,
+                name: &slot.name,
+                ctx: Some(ctx),
+                args: Some(args),
+            },
+        }
+    }
+
+    fn error(error: Error<V>) -> Self {
+        AsyncMethodCallFuture {
+            state: AsyncMethodCallFutureState::Failed { error: Some(error) },
+        }
+    }
+}
+
 #[cfg(feature = "async")]
 impl<'a, S: ServiceType, T: AsyncStateType<V>, C, V: Value> core::future::Future
     for AsyncMethodCallFuture<'a, S, T, C, V>
@@ -296,22 +837,23 @@ where
             let mut this = self.as_mut().project();
             match this.state.as_mut().project() {
                 Proj::Init {
-                    methods,
-                    state,
+                    method,
+                    source,
+                    mut state,
                     ctx,
                     name,
                     args,
                 } => {
-                    let mut state = match ready!(state.poll(cx)) {
+                    if state.is_none() {
+                        state.set(Some(source.get()));
+                    }
+                    let pending = state.as_pin_mut().expect("state");
+                    let mut state = match ready!(pending.poll(cx)) {
                         Ok(ret) => ret,
                         Err(err) => return core::task::Poll::Ready(Err(err.into())),
                     };
 
-                    let Some(method) = methods.get(*name) else {
-                        this.state.set(AsyncMethodCallFutureState::Done);
-                        return core::task::Poll::Ready(Err(Error::MethodNotFound));
-                    };
-
+                    let method: &'a S::Callable<T::State, C, V> = method;
                     let ctx = ctx.take().expect("ctx");
                     let args = args.take().expect("args");
                     // let state = state.take().expect("state");
@@ -321,15 +863,26 @@ where
                     let future =
                         method.call_async(unsafe { &mut *unsafe_state }.get_mut(), ctx, args);
 
-                    this.state
-                        .set(AsyncMethodCallFutureState::Call { state, future })
+                    let name: &'a str = name;
+
+                    this.state.set(AsyncMethodCallFutureState::Call {
+                        state,
+                        future,
+                        name,
+                    })
                 }
-                Proj::Call { future, .. } => {
+                Proj::Call { future, name, .. } => {
                     let ret = futures_core::ready!(future.poll(cx));
+                    let frame = Frame::Method((*name).into());
 
                     this.state.set(AsyncMethodCallFutureState::Done);
 
-                    return core::task::Poll::Ready(ret);
+                    return core::task::Poll::Ready(ret.context(frame));
+                }
+                Proj::Failed { error } => {
+                    let error = error.take().expect("poll after done");
+                    this.state.set(AsyncMethodCallFutureState::Done);
+                    return core::task::Poll::Ready(Err(error));
                 }
                 Proj::Done => {
                     panic!("poll after done")
@@ -341,7 +894,7 @@ where
 
 #[cfg(feature = "async")]
 pin_project! {
-    pub struct GetFuture<'a, S: 'a,V> where S: AsyncStateType<V>, V: Value  {
+    pub struct GetFuture<'a, S, V> where S: AsyncStateType<V>, S: 'a, V: Value {
         #[pin]
         future: S::Future<'a>,
         name: &'a str,
@@ -363,7 +916,7 @@ where
     ) -> core::task::Poll<Self::Output> {
         let this = self.project();
         match ready!(this.future.poll(cx)) {
-            Ok(mut ret) => core::task::Poll::Ready(ret.get_mut().get(&this.name)),
+            Ok(mut ret) => core::task::Poll::Ready(ret.get_mut().get(this.name)),
             Err(err) => core::task::Poll::Ready(Err(err.into())),
         }
     }
@@ -371,7 +924,7 @@ where
 
 #[cfg(feature = "async")]
 pin_project! {
-    pub struct SetFuture<'a, S: 'a, V> where S: AsyncStateType<V>, V: Value {
+    pub struct SetFuture<'a, S, V> where S: AsyncStateType<V>, S: 'a, V: Value {
         #[pin]
         future: S::Future<'a>,
         name: &'a str,
@@ -396,9 +949,220 @@ where
         match ready!(this.future.poll(cx)) {
             Ok(mut ret) => core::task::Poll::Ready(
                 ret.get_mut()
-                    .set(&this.name, this.value.take().expect("value")),
+                    .set(this.name, this.value.take().expect("value")),
             ),
             Err(err) => core::task::Poll::Ready(Err(err.into())),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        collections::BTreeMap,
+        string::{String, ToString},
+    };
+
+    use super::*;
+    use crate::testing::Value;
+
+    type Map = BTreeMap<String, Value>;
+
+    fn service() -> DynService<SyncState<Map>, Sync, (), Value> {
+        DynService::new(SyncState::new(Map::default()))
+    }
+
+    fn fail(_this: &mut Map, _ctx: &mut (), _args: Arguments<Value>) -> Result<i64, Error<Value>> {
+        Err(Error::new("failed"))
+    }
+
+    /// Counts how often its signature is taken
+    struct Counted(alloc::sync::Arc<core::sync::atomic::AtomicUsize>);
+
+    impl MethodCallable<Map, (), Value> for Counted {
+        fn signature(&self) -> Signature<Value> {
+            self.0.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+            Signature::default()
+        }
+
+        fn call(
+            &self,
+            _this: &mut Map,
+            _ctx: &mut (),
+            _args: Arguments<Value>,
+        ) -> Result<Value, Error<Value>> {
+            Ok(Value::Void)
+        }
+    }
+
+    #[test]
+    fn signatures_are_taken_once_at_registration() {
+        let count = alloc::sync::Arc::new(core::sync::atomic::AtomicUsize::new(0));
+        let mut service = service();
+        service.register("counted", Counted(count.clone()));
+
+        for _ in 0..2 {
+            assert!(service.signature().get("counted").is_some());
+        }
+        Service::call(&service, &mut (), "counted", Arguments::default()).unwrap();
+        assert_eq!(count.load(core::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    fn zero(_this: &mut Map, _ctx: &mut (), _args: Arguments<Value>) -> Result<i64, Error<Value>> {
+        Ok(0)
+    }
+
+    #[test]
+    fn handles_go_stale_when_the_method_is_registered_again() {
+        let mut service = service();
+        service.register("method", fail);
+        let stale = service.resolve("method").unwrap();
+        let err = Service::call_by_id(&service, &mut (), &stale, Arguments::default()).unwrap_err();
+        assert!(matches!(err.root(), Error::Runtime(_)));
+
+        service.register("method", zero);
+        assert!(matches!(
+            Service::call_by_id(&service, &mut (), &stale, Arguments::default()),
+            Err(Error::StaleMethod)
+        ));
+
+        let id = service.resolve("method").unwrap();
+        assert_ne!(id, stale);
+        let ret = Service::call_by_id(&service, &mut (), &id, Arguments::default());
+        assert_eq!(ret.unwrap(), Value::Int(0));
+    }
+
+    #[test]
+    fn handles_pass_through_exclusive_services() {
+        let mut service: DynService<SyncState<Map>, SharedContext, (), Value> =
+            DynService::new_shared(SyncState::new(Map::default()));
+        service.register(
+            "zero",
+            |_this: &mut Map, _ctx: &(), _args: Arguments<Value>| Ok::<_, Error<Value>>(0i64),
+        );
+        let service = crate::service::ExclusiveService::new(service);
+
+        let id = Service::resolve(&service, "zero").unwrap();
+        let ret = Service::call_by_id(&service, &mut (), &id, Arguments::default());
+        assert_eq!(ret.unwrap(), Value::Int(0));
+
+        // Calling by name would have succeeded, so the handle reached the service
+        let stale = MethodId::new(id.index(), id.generation() + 1, "zero".into());
+        assert!(matches!(
+            Service::call_by_id(&service, &mut (), &stale, Arguments::default()),
+            Err(Error::StaleMethod)
+        ));
+    }
+
+    #[test]
+    fn unknown_methods_are_named_in_a_frame() {
+        let err = Service::call(&service(), &mut (), "nope", Arguments::default()).unwrap_err();
+        assert_eq!(
+            err.frames().collect::<Vec<_>>(),
+            [&Frame::Method("nope".into())]
+        );
+        assert!(matches!(err.root(), Error::MethodNotFound));
+        assert_eq!(err.to_string(), "method `nope`: method not found");
+    }
+
+    #[test]
+    fn method_errors_are_named_in_a_frame() {
+        let mut service = service();
+        service.register("fail", fail);
+
+        let err = Service::call(&service, &mut (), "fail", Arguments::default()).unwrap_err();
+        assert!(matches!(err, Error::Context { .. }));
+        assert!(matches!(err.root(), Error::Runtime(_)));
+        assert_eq!(err.to_string(), "method `fail`: failed");
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use futures::{
+            executor::{block_on, block_on_stream},
+            stream,
+        };
+
+        use super::*;
+
+        fn one(
+            _this: &mut Map,
+            _ctx: &mut (),
+            _args: Arguments<Value>,
+        ) -> Result<i64, Error<Value>> {
+            Ok(1)
+        }
+
+        fn ones(
+            _this: &mut Map,
+            _ctx: &mut (),
+            _args: Arguments<Value>,
+        ) -> impl futures_core::Stream<Item = i64> {
+            stream::iter([1, 1])
+        }
+
+        #[test]
+        fn streams_are_listed_and_opened() {
+            let mut service = service();
+            service.register_stream("ones", ones);
+            assert!(service.signature().get("ones").unwrap().is_stream());
+
+            let mut ctx = ();
+            let stream = service
+                .call_stream(&mut ctx, "ones", Arguments::default())
+                .unwrap();
+            let items = block_on_stream(stream)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>();
+            assert_eq!(items, [Value::Int(1), Value::Int(1)]);
+        }
+
+        #[test]
+        fn calls_lock_the_state_when_polled() {
+            let mut service = DynService::new_async(crate::service::SendState::new(Map::default()));
+            service.register(
+                "one",
+                |_this: &mut Map, _ctx: &mut (), _args: Arguments<Value>| async {
+                    Ok::<_, Error<Value>>(1i64)
+                },
+            );
+
+            // Both calls are created before either runs
+            let (mut a, mut b) = ((), ());
+            let first = AsyncService::call(&service, &mut a, "one", Arguments::default());
+            let second = AsyncService::call(&service, &mut b, "one", Arguments::default());
+            assert_eq!(block_on(first).unwrap(), Value::Int(1));
+            assert_eq!(block_on(second).unwrap(), Value::Int(1));
+        }
+
+        #[test]
+        fn streams_cannot_take_a_method_name() {
+            let mut service = service();
+            let err = service
+                .register("one", one)
+                .try_register_stream("one", ones)
+                .err()
+                .unwrap();
+            assert!(matches!(err, Error::NameTaken(ref name) if name == "one"));
+            assert!(!service.signature().get("one").unwrap().is_stream());
+        }
+
+        #[test]
+        fn methods_cannot_take_a_stream_name() {
+            let mut service = service();
+            let err = service
+                .register_stream("one", ones)
+                .try_register("one", one)
+                .err()
+                .unwrap();
+            assert_eq!(err.to_string(), "`one` is already registered");
+            assert!(service.signature().get("one").unwrap().is_stream());
+        }
+
+        #[test]
+        #[should_panic = "`one` is already registered"]
+        fn register_panics_on_a_taken_name() {
+            service().register_stream("one", ones).register("one", one);
+        }
+    }
+}
@@ -0,0 +1,714 @@
+use alloc::{boxed::Box, string::String, sync::Arc};
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures_core::ready;
+use hashbrown::HashMap;
+use pin_project_lite::pin_project;
+
+use super::{service::MethodRef, AsyncService, MethodId, ServiceSignature};
+use crate::{arguments::Arguments, time::Timer, Error, TimeoutFuture, Value};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// What to do with a call when the limit is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Fail immediately
+    Reject,
+    /// Wait until the call can proceed
+    Wait,
+    /// Wait at most the given duration before failing
+    WaitFor(Duration),
+}
+
+/// Bounds the number of in-flight calls, globally and per method.
+/// Calls over the limit fail with `Error::Overloaded` according to the `Overflow` policy
+pub struct ConcurrencyLimit<S, T> {
+    service: S,
+    global: Option<Arc<Semaphore>>,
+    methods: HashMap<String, Arc<Semaphore>>,
+    overflow: Overflow,
+    _timer: PhantomData<T>,
+}
+
+impl<S, T> ConcurrencyLimit<S, T> {
+    /// Calls over a limit wait for a permit (`Overflow::Wait`) until another policy is set.
+    /// Unlike `RateLimit`, which rejects, since permits come back as soon as calls complete
+    pub fn new(service: S) -> ConcurrencyLimit<S, T> {
+        ConcurrencyLimit {
+            service,
+            global: None,
+            methods: HashMap::default(),
+            overflow: Overflow::Wait,
+            _timer: PhantomData,
+        }
+    }
+
+    /// Allow at most `limit` calls in flight across all methods
+    pub fn global(mut self, limit: usize) -> Self {
+        self.global = Some(Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    /// Allow at most `limit` calls to `name` in flight
+    pub fn method(mut self, name: impl Into<String>, limit: usize) -> Self {
+        self.methods
+            .insert(name.into(), Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+pub struct Permits {
+    _global: Option<SemaphoreGuardArc>,
+    _method: Option<SemaphoreGuardArc>,
+}
+
+async fn acquire<V: Value>(
+    semaphore: Arc<Semaphore>,
+    overflow: Overflow,
+) -> Result<SemaphoreGuardArc, Error<V>> {
+    match overflow {
+        Overflow::Reject => semaphore.try_acquire_arc().ok_or(Error::Overloaded),
+        Overflow::Wait | Overflow::WaitFor(_) => Ok(semaphore.acquire_arc().await),
+    }
+}
+
+/// Takes the permits in a fixed order, global first, so waiting calls cannot deadlock.
+/// `Overflow::WaitFor` bounds the wait for both permits together
+async fn acquire_permits<T, V>(
+    global: Option<Arc<Semaphore>>,
+    method: Option<Arc<Semaphore>>,
+    overflow: Overflow,
+) -> Result<Permits, Error<V>>
+where
+    T: Timer,
+    V: Value,
+{
+    let permits = async move {
+        let global = match global {
+            Some(semaphore) => Some(acquire(semaphore, overflow).await?),
+            None => None,
+        };
+        let method = match method {
+            Some(semaphore) => Some(acquire(semaphore, overflow).await?),
+            None => None,
+        };
+        Ok(Permits {
+            _global: global,
+            _method: method,
+        })
+    };
+
+    match overflow {
+        Overflow::WaitFor(duration) => TimeoutFuture::new(permits, duration, T::sleep)
+            .await
+            .map_err(|err| match err {
+                Error::Timeout => Error::Overloaded,
+                err => err,
+            }),
+        _ => permits.await,
+    }
+}
+
+impl<S, T, C, V> AsyncService<C, V> for ConcurrencyLimit<S, T>
+where
+    S: AsyncService<C, V>,
+    T: Timer + 'static,
+    T::Sleep: Send,
+    V: Value + Send + 'static,
+{
+    type Call<'a>
+        = LimitFuture<'a, S, C, V, Permits>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
+        let permits = self.permits(name);
+        LimitFuture::new(&self.service, ctx, MethodRef::Name(name), args, permits)
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        let permits = self.permits(id.name());
+        LimitFuture::new(&self.service, ctx, MethodRef::Id(id), args, permits)
+    }
+}
+
+impl<S, T> ConcurrencyLimit<S, T>
+where
+    T: Timer + 'static,
+    T::Sleep: Send,
+{
+    fn permits<'a, V>(&self, name: &str) -> BoxFuture<'a, Result<Permits, Error<V>>>
+    where
+        V: Value + Send + 'static,
+    {
+        Box::pin(acquire_permits::<T, V>(
+            self.global.clone(),
+            self.methods.get(name).cloned(),
+            self.overflow,
+        ))
+    }
+}
+
+#[cfg(feature = "std")]
+mod rate {
+    use super::*;
+    use std::{sync::Mutex, time::Instant};
+
+    /// `burst` calls, replenished evenly over `period`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Quota {
+        pub burst: u32,
+        pub period: Duration,
+    }
+
+    impl Quota {
+        pub fn new(burst: u32, period: Duration) -> Quota {
+            Quota {
+                burst: burst.max(1),
+                period,
+            }
+        }
+
+        pub fn per_second(calls: u32) -> Quota {
+            Quota::new(calls, Duration::from_secs(1))
+        }
+    }
+
+    /// Tests run the buckets on tokio's clock, which they pause
+    fn now() -> Instant {
+        #[cfg(all(test, feature = "tokio"))]
+        return tokio::time::Instant::now().into_std();
+        #[cfg(not(all(test, feature = "tokio")))]
+        Instant::now()
+    }
+
+    struct Bucket {
+        quota: Quota,
+        tokens: f64,
+        last: Instant,
+    }
+
+    impl Bucket {
+        fn new(quota: Quota) -> Bucket {
+            Bucket {
+                quota,
+                tokens: quota.burst as f64,
+                last: now(),
+            }
+        }
+
+        /// Take a token, or return how long until one is available
+        fn take(&mut self) -> Result<(), Duration> {
+            let now = now();
+            let burst = self.quota.burst as f64;
+            let rate = burst / self.quota.period.as_secs_f64();
+
+            self.tokens =
+                (self.tokens + now.duration_since(self.last).as_secs_f64() * rate).min(burst);
+            self.last = now;
+
+            if self.tokens >= 1.0 {
+                self.tokens -= 1.0;
+                Ok(())
+            } else {
+                Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+            }
+        }
+
+        /// Return a token taken for a call that did not go through
+        fn give_back(&mut self) {
+            self.tokens = (self.tokens + 1.0).min(self.quota.burst as f64);
+        }
+    }
+
+    type SharedBucket = Arc<Mutex<Bucket>>;
+
+    fn lock(bucket: &SharedBucket) -> std::sync::MutexGuard<'_, Bucket> {
+        bucket.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// `waited` is shared by the buckets of a call,
+    /// so `Overflow::WaitFor` bounds the wait for all of them together
+    async fn take<T, V>(
+        bucket: &SharedBucket,
+        overflow: Overflow,
+        waited: &mut Duration,
+    ) -> Result<(), Error<V>>
+    where
+        T: Timer,
+        V: Value,
+    {
+        loop {
+            let wait = match lock(bucket).take() {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+
+            match overflow {
+                Overflow::Reject => return Err(Error::RateLimited),
+                Overflow::WaitFor(max) if *waited + wait > max => return Err(Error::RateLimited),
+                _ => {}
+            }
+
+            T::sleep(wait).await;
+            *waited += wait;
+        }
+    }
+
+    /// Limits the call rate with token buckets, globally and per method.
+    /// Calls over the limit fail with `Error::RateLimited` according to the `Overflow` policy
+    pub struct RateLimit<S, T> {
+        service: S,
+        global: Option<SharedBucket>,
+        methods: HashMap<String, SharedBucket>,
+        overflow: Overflow,
+        _timer: PhantomData<T>,
+    }
+
+    impl<S, T> RateLimit<S, T> {
+        /// Calls over a quota are rejected (`Overflow::Reject`) until another policy is set.
+        /// Unlike `ConcurrencyLimit`, which waits, since a token may be a whole period away
+        pub fn new(service: S) -> RateLimit<S, T> {
+            RateLimit {
+                service,
+                global: None,
+                methods: HashMap::default(),
+                overflow: Overflow::Reject,
+                _timer: PhantomData,
+            }
+        }
+
+        pub fn global(mut self, quota: Quota) -> Self {
+            self.global = Some(Arc::new(Mutex::new(Bucket::new(quota))));
+            self
+        }
+
+        pub fn method(mut self, name: impl Into<String>, quota: Quota) -> Self {
+            self.methods
+                .insert(name.into(), Arc::new(Mutex::new(Bucket::new(quota))));
+            self
+        }
+
+        pub fn overflow(mut self, overflow: Overflow) -> Self {
+            self.overflow = overflow;
+            self
+        }
+
+        pub fn into_inner(self) -> S {
+            self.service
+        }
+    }
+
+    impl<S, T, C, V> AsyncService<C, V> for RateLimit<S, T>
+    where
+        S: AsyncService<C, V>,
+        T: Timer + 'static,
+        T::Sleep: Send,
+        V: Value + Send + 'static,
+    {
+        type Call<'a>
+            = LimitFuture<'a, S, C, V, ()>
+        where
+            Self: 'a,
+            C: 'a;
+
+        fn signature(&self) -> ServiceSignature<V> {
+            self.service.signature()
+        }
+
+        fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
+            let permit = self.permit(name);
+            LimitFuture::new(&self.service, ctx, MethodRef::Name(name), args, permit)
+        }
+
+        fn resolve(&self, name: &str) -> Option<MethodId> {
+            self.service.resolve(name)
+        }
+
+        fn call_by_id<'a>(
+            &'a self,
+            ctx: &'a mut C,
+            id: &'a MethodId,
+            args: Arguments<V>,
+        ) -> Self::Call<'a> {
+            let permit = self.permit(id.name());
+            LimitFuture::new(&self.service, ctx, MethodRef::Id(id), args, permit)
+        }
+    }
+
+    impl<S, T> RateLimit<S, T>
+    where
+        T: Timer + 'static,
+        T::Sleep: Send,
+    {
+        fn permit<'a, V>(&self, name: &str) -> BoxFuture<'a, Result<(), Error<V>>>
+        where
+            V: Value + Send + 'static,
+        {
+            let global = self.global.clone();
+            let method = self.methods.get(name).cloned();
+            let overflow = self.overflow;
+
+            Box::pin(async move {
+                let mut waited = Duration::ZERO;
+                if let Some(bucket) = &global {
+                    take::<T, V>(bucket, overflow, &mut waited).await?;
+                }
+                if let Some(bucket) = &method {
+                    if let Err(err) = take::<T, V>(bucket, overflow, &mut waited).await {
+                        // The call is not made, so it should not count against the global quota
+                        if let Some(bucket) = &global {
+                            lock(bucket).give_back();
+                        }
+                        return Err(err);
+                    }
+                }
+                Ok(())
+            })
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use rate::{Quota, RateLimit};
+
+pin_project! {
+    #[project = LimitProj]
+    enum LimitState<'a, S, C, V, P>
+    where
+        S: AsyncService<C, V>,
+        S: 'a,
+        C: 'a,
+        V: Value,
+    {
+        Acquire {
+            permit: BoxFuture<'a, Result<P, Error<V>>>,
+            service: &'a S,
+            ctx: Option<&'a mut C>,
+            method: MethodRef<'a>,
+            args: Option<Arguments<V>>,
+        },
+        Call {
+            permit: P,
+            #[pin]
+            future: S::Call<'a>,
+        },
+        Done,
+    }
+}
+
+pin_project! {
+    /// Waits for a permit, then calls the service while holding it
+    pub struct LimitFuture<'a, S, C, V, P>
+    where
+        S: AsyncService<C, V>,
+        S: 'a,
+        C: 'a,
+        V: Value,
+    {
+        #[pin]
+        state: LimitState<'a, S, C, V, P>,
+    }
+}
+
+impl<'a, S, C, V, P> LimitFuture<'a, S, C, V, P>
+where
+    S: AsyncService<C, V>,
+    V: Value,
+{
+    fn new(
+        service: &'a S,
+        ctx: &'a mut C,
+        method: MethodRef<'a>,
+        args: Arguments<V>,
+        permit: BoxFuture<'a, Result<P, Error<V>>>,
+    ) -> Self {
+        LimitFuture {
+            state: LimitState::Acquire {
+                permit,
+                service,
+                ctx: Some(ctx),
+                method,
+                args: Some(args),
+            },
+        }
+    }
+}
+
+impl<'a, S, C, V, P> Future for LimitFuture<'a, S, C, V, P>
+where
+    S: AsyncService<C, V>,
+    V: Value,
+{
+    type Output = Result<V, Error<V>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let mut this = self.as_mut().project();
+            match this.state.as_mut().project() {
+                LimitProj::Acquire {
+                    permit,
+                    service,
+                    ctx,
+                    method,
+                    args,
+                } => {
+                    let permit = match ready!(permit.as_mut().poll(cx)) {
+                        Ok(permit) => permit,
+                        Err(err) => {
+                            this.state.set(LimitState::Done);
+                            return Poll::Ready(Err(err));
+                        }
+                    };
+
+                    let service: &'a S = service;
+                    let ctx = ctx.take().expect("context");
+                    let args = args.take().expect("arguments");
+
+                    let future = method.call(service, ctx, args);
+                    this.state.set(LimitState::Call { permit, future });
+                }
+                LimitProj::Call { future, .. } => {
+                    let ret = ready!(future.poll(cx));
+                    // Releases the permit
+                    this.state.set(LimitState::Done);
+                    return Poll::Ready(ret);
+                }
+                LimitProj::Done => panic!("poll after done"),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use core::future::{ready, Ready};
+    use tokio::time::Instant;
+
+    use super::*;
+    use crate::{executor::Tokio, testing::Value};
+
+    /// Answers every call right away
+    struct Echo;
+
+    impl AsyncService<(), Value> for Echo {
+        type Call<'a> = Ready<Result<Value, Error<Value>>>;
+
+        fn signature(&self) -> ServiceSignature<Value> {
+            ServiceSignature::default()
+        }
+
+        fn call<'a>(
+            &'a self,
+            _ctx: &'a mut (),
+            name: &'a str,
+            _args: Arguments<Value>,
+        ) -> Self::Call<'a> {
+            ready(Ok(name.into()))
+        }
+
+        fn resolve(&self, name: &str) -> Option<MethodId> {
+            Some(MethodId::new(0, 0, name.into()))
+        }
+
+        fn call_by_id<'a>(
+            &'a self,
+            _ctx: &'a mut (),
+            _id: &'a MethodId,
+            _args: Arguments<Value>,
+        ) -> Self::Call<'a> {
+            ready(Ok("by id".into()))
+        }
+    }
+
+    /// Time is paused, and jumps ahead to the next timer whenever all tasks wait
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .unwrap()
+    }
+
+    async fn call<S: AsyncService<(), Value>>(
+        service: &S,
+        name: &str,
+    ) -> Result<Value, Error<Value>> {
+        service.call(&mut (), name, Arguments::default()).await
+    }
+
+    async fn call_by_id<S: AsyncService<(), Value>>(
+        service: &S,
+        name: &str,
+    ) -> Result<Value, Error<Value>> {
+        let id = service.resolve(name).expect("a handle");
+        service.call_by_id(&mut (), &id, Arguments::default()).await
+    }
+
+    /// Drops `guard` after `delay`
+    fn release(guard: SemaphoreGuardArc, delay: Duration) {
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            drop(guard);
+        });
+    }
+
+    #[test]
+    fn reject_fails_calls_over_the_limit() {
+        let service = ConcurrencyLimit::<_, Tokio>::new(Echo)
+            .method("echo", 1)
+            .overflow(Overflow::Reject);
+        let _held = service.methods["echo"].try_acquire_arc().unwrap();
+
+        runtime().block_on(async {
+            assert!(matches!(
+                call(&service, "echo").await,
+                Err(Error::Overloaded)
+            ));
+            assert_eq!(call(&service, "other").await.unwrap(), Value::from("other"));
+        });
+    }
+
+    #[test]
+    fn handles_are_passed_on_and_limited() {
+        let service = ConcurrencyLimit::<_, Tokio>::new(Echo)
+            .method("echo", 1)
+            .overflow(Overflow::Reject);
+
+        runtime().block_on(async {
+            assert_eq!(
+                call_by_id(&service, "echo").await.unwrap(),
+                Value::from("by id")
+            );
+
+            let _held = service.methods["echo"].try_acquire_arc().unwrap();
+            assert!(matches!(
+                call_by_id(&service, "echo").await,
+                Err(Error::Overloaded)
+            ));
+        });
+    }
+
+    #[test]
+    fn wait_for_is_one_deadline_for_both_permits() {
+        let service = ConcurrencyLimit::<_, Tokio>::new(Echo)
+            .global(1)
+            .method("echo", 1)
+            .overflow(Overflow::WaitFor(Duration::from_millis(200)));
+
+        runtime().block_on(async {
+            // Either permit alone frees up in time, but not both together
+            release(
+                service.global.as_ref().unwrap().try_acquire_arc().unwrap(),
+                Duration::from_millis(150),
+            );
+            release(
+                service.methods["echo"].try_acquire_arc().unwrap(),
+                Duration::from_millis(350),
+            );
+
+            let start = Instant::now();
+            assert!(matches!(
+                call(&service, "echo").await,
+                Err(Error::Overloaded)
+            ));
+            assert!(start.elapsed() >= Duration::from_millis(200));
+            assert!(start.elapsed() < Duration::from_millis(350));
+        });
+    }
+
+    #[cfg(feature = "std")]
+    mod rate {
+        use super::*;
+
+        const HOUR: Duration = Duration::from_secs(60 * 60);
+
+        #[test]
+        fn rejected_calls_give_the_global_token_back() {
+            let service = RateLimit::<_, Tokio>::new(Echo)
+                .global(Quota::new(2, HOUR))
+                .method("echo", Quota::new(1, HOUR));
+
+            runtime().block_on(async {
+                assert!(call(&service, "echo").await.is_ok());
+                assert!(matches!(
+                    call(&service, "echo").await,
+                    Err(Error::RateLimited)
+                ));
+                // The rejected call did not use up the last global token
+                assert!(call(&service, "other").await.is_ok());
+                assert!(matches!(
+                    call(&service, "other").await,
+                    Err(Error::RateLimited)
+                ));
+            });
+        }
+
+        #[test]
+        fn handles_are_passed_on_and_limited() {
+            let service = RateLimit::<_, Tokio>::new(Echo).method("echo", Quota::new(1, HOUR));
+
+            runtime().block_on(async {
+                assert_eq!(
+                    call_by_id(&service, "echo").await.unwrap(),
+                    Value::from("by id")
+                );
+                assert!(matches!(
+                    call_by_id(&service, "echo").await,
+                    Err(Error::RateLimited)
+                ));
+            });
+        }
+
+        #[test]
+        fn wait_for_is_one_deadline_for_both_buckets() {
+            let quota = Quota::new(1, Duration::from_millis(150));
+            let service = RateLimit::<_, Tokio>::new(Echo)
+                .global(quota)
+                .method("echo", Quota::new(1, Duration::from_millis(300)))
+                .overflow(Overflow::WaitFor(Duration::from_millis(200)));
+
+            runtime().block_on(async {
+                assert!(call(&service, "echo").await.is_ok());
+                // The global token is back after 150ms, the method one 150ms later
+                let start = Instant::now();
+                assert!(matches!(
+                    call(&service, "echo").await,
+                    Err(Error::RateLimited)
+                ));
+                // Given up as soon as the waits add up past the deadline
+                assert!(start.elapsed() < Duration::from_millis(200));
+            });
+        }
+    }
+}
@@ -1,16 +1,40 @@
+#[cfg(feature = "async")]
+mod batch;
 mod boxed;
 mod dyn_service;
+#[cfg(feature = "async")]
+mod limit;
 mod method;
+mod observe;
+mod path;
+#[cfg(feature = "snapshot")]
+mod persist;
+#[allow(clippy::module_inception)]
 mod service;
+mod shared;
 mod state;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "async")]
+mod timeout;
+mod transaction;
 
 pub use self::{
-    boxed::*,
     dyn_service::*,
     method::*,
+    observe::*,
+    path::*,
     service::*,
-    state::{HasState, SendState, State, SyncState},
+    shared::*,
+    state::{HasState, SendState, State, StateField, StateFields, SyncState},
+    transaction::*,
 };
 
 #[cfg(feature = "async")]
-pub use self::state::AsyncState;
+pub use self::{batch::*, boxed::*, limit::*, state::AsyncState, stream::*, timeout::*};
+
+#[cfg(feature = "derive")]
+pub use gerning_derive::State;
+
+#[cfg(feature = "snapshot")]
+pub use self::persist::*;
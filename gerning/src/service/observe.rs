@@ -0,0 +1,408 @@
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use avagarden::sync::Mutex;
+use locket::{LockApi, LockApiWriteGuard, LockError};
+
+use super::State;
+use crate::{Error, Value};
+
+#[cfg(feature = "async")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "async")]
+use core::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+#[cfg(feature = "async")]
+use futures_core::Stream;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateChange<V> {
+    pub name: String,
+    pub old: Option<V>,
+    pub new: V,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscription(u64);
+
+type Callback<V> = Arc<dyn Fn(&StateChange<V>) + Send + Sync>;
+
+/// Changes a stream buffers before it drops the oldest, see `Observer::stream_with_capacity`
+#[cfg(feature = "async")]
+pub const STREAM_CAPACITY: usize = 64;
+
+struct Listeners<V> {
+    next_id: u64,
+    callbacks: Vec<(u64, Callback<V>)>,
+    #[cfg(feature = "async")]
+    streams: Vec<Arc<Mutex<Channel<V>>>>,
+    /// Set when the observed state is dropped
+    #[cfg(feature = "async")]
+    closed: bool,
+}
+
+impl<V> Listeners<V> {
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+#[cfg(feature = "async")]
+impl<V> Listeners<V> {
+    fn close(&mut self) {
+        self.closed = true;
+        for channel in self.streams.drain(..) {
+            if let Ok(mut channel) =
+                <Arc<Mutex<Channel<V>>> as LockApi<Channel<V>>>::write(&channel)
+            {
+                channel.get_mut().close();
+            }
+        }
+    }
+}
+
+/// Handle used to subscribe to changes of an `Observable` state.
+/// The handle can be cloned and kept after the state has been moved into a service.
+pub struct Observer<V> {
+    listeners: Arc<Mutex<Listeners<V>>>,
+}
+
+impl<V> Clone for Observer<V> {
+    fn clone(&self) -> Self {
+        Observer {
+            listeners: self.listeners.clone(),
+        }
+    }
+}
+
+impl<V> Observer<V> {
+    fn new() -> Observer<V> {
+        Observer {
+            listeners: Arc::new(Mutex::new(Listeners {
+                next_id: 0,
+                callbacks: Vec::default(),
+                #[cfg(feature = "async")]
+                streams: Vec::default(),
+                #[cfg(feature = "async")]
+                closed: false,
+            })),
+        }
+    }
+
+    pub fn subscribe<F>(&self, callback: F) -> Result<Subscription, LockError>
+    where
+        F: Fn(&StateChange<V>) + Send + Sync + 'static,
+    {
+        let mut lock = <Arc<Mutex<Listeners<V>>> as LockApi<Listeners<V>>>::write(&self.listeners)?;
+        let listeners = lock.get_mut();
+        let id = listeners.next_id();
+        listeners.callbacks.push((id, Arc::new(callback)));
+        Ok(Subscription(id))
+    }
+
+    pub fn unsubscribe(&self, subscription: Subscription) -> Result<bool, LockError> {
+        let mut lock = <Arc<Mutex<Listeners<V>>> as LockApi<Listeners<V>>>::write(&self.listeners)?;
+        let listeners = lock.get_mut();
+        let len = listeners.callbacks.len();
+        listeners.callbacks.retain(|(id, _)| *id != subscription.0);
+        Ok(listeners.callbacks.len() != len)
+    }
+
+    /// A stream of every change made after this call, buffering up to `STREAM_CAPACITY` changes.
+    /// The stream ends when the observed state is dropped
+    #[cfg(feature = "async")]
+    pub fn stream(&self) -> Result<StateStream<V>, LockError> {
+        self.stream_with_capacity(STREAM_CAPACITY)
+    }
+
+    /// Like `stream`, but a stream falling more than `capacity` changes behind
+    /// drops the oldest ones
+    #[cfg(feature = "async")]
+    pub fn stream_with_capacity(&self, capacity: usize) -> Result<StateStream<V>, LockError> {
+        let mut lock = <Arc<Mutex<Listeners<V>>> as LockApi<Listeners<V>>>::write(&self.listeners)?;
+        let listeners = lock.get_mut();
+
+        let channel = Arc::new(Mutex::new(Channel {
+            queue: VecDeque::default(),
+            capacity: capacity.max(1),
+            waker: None,
+            closed: listeners.closed,
+        }));
+        if !listeners.closed {
+            listeners.streams.push(channel.clone());
+        }
+
+        Ok(StateStream { channel })
+    }
+
+    fn emit(&self, change: StateChange<V>)
+    where
+        V: Clone,
+    {
+        let Ok(mut lock) =
+            <Arc<Mutex<Listeners<V>>> as LockApi<Listeners<V>>>::write(&self.listeners)
+        else {
+            return;
+        };
+
+        let listeners = lock.get_mut();
+
+        // Callbacks are invoked without holding the lock,
+        // so they are free to subscribe or unsubscribe
+        let callbacks = listeners
+            .callbacks
+            .iter()
+            .map(|(_, cb)| cb.clone())
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "async")]
+        listeners.streams.retain(|channel| {
+            match <Arc<Mutex<Channel<V>>> as LockApi<Channel<V>>>::write(channel) {
+                Ok(mut channel) => channel.get_mut().push(change.clone()),
+                Err(_) => false,
+            }
+        });
+
+        drop(lock);
+
+        for callback in callbacks {
+            callback(&change);
+        }
+    }
+}
+
+/// Wraps a state and notifies subscribers on every `State::set`
+pub struct Observable<T, V> {
+    state: T,
+    observer: Owner<V>,
+}
+
+/// The observer held by the state itself.
+/// Observers outlive the state, so their streams are closed when it goes
+struct Owner<V>(Observer<V>);
+
+#[cfg(feature = "async")]
+impl<V> Drop for Owner<V> {
+    fn drop(&mut self) {
+        if let Ok(mut lock) =
+            <Arc<Mutex<Listeners<V>>> as LockApi<Listeners<V>>>::write(&self.0.listeners)
+        {
+            lock.get_mut().close();
+        }
+    }
+}
+
+impl<T, V> Observable<T, V> {
+    pub fn new(state: T) -> Observable<T, V> {
+        Observable {
+            state,
+            observer: Owner(Observer::new()),
+        }
+    }
+
+    pub fn observer(&self) -> Observer<V> {
+        self.observer.0.clone()
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.state
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.state
+    }
+
+    pub fn into_inner(self) -> T {
+        self.state
+    }
+}
+
+impl<T, V> State<V> for Observable<T, V>
+where
+    T: State<V>,
+    V: Value + Clone,
+{
+    fn set(&mut self, name: &str, value: V) -> Result<(), Error<V>> {
+        let old = self.state.get(name)?;
+        self.state.set(name, value.clone())?;
+        self.observer.0.emit(StateChange {
+            name: name.to_string(),
+            old,
+            new: value,
+        });
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Option<V>, Error<V>> {
+        self.state.get(name)
+    }
+}
+
+#[cfg(feature = "async")]
+struct Channel<V> {
+    queue: VecDeque<StateChange<V>>,
+    capacity: usize,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+#[cfg(feature = "async")]
+impl<V> Channel<V> {
+    /// Returns false if the receiving end is gone
+    fn push(&mut self, change: StateChange<V>) -> bool {
+        if self.closed {
+            return false;
+        }
+        if self.queue.len() == self.capacity {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(change);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+        true
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub struct StateStream<V> {
+    channel: Arc<Mutex<Channel<V>>>,
+}
+
+#[cfg(feature = "async")]
+impl<V> Stream for StateStream<V> {
+    type Item = StateChange<V>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let Ok(mut lock) = <Arc<Mutex<Channel<V>>> as LockApi<Channel<V>>>::write(&self.channel)
+        else {
+            return Poll::Ready(None);
+        };
+
+        let channel = lock.get_mut();
+
+        if let Some(change) = channel.queue.pop_front() {
+            return Poll::Ready(Some(change));
+        }
+
+        if channel.closed {
+            return Poll::Ready(None);
+        }
+
+        channel.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl<V> Drop for StateStream<V> {
+    fn drop(&mut self) {
+        if let Ok(mut lock) = <Arc<Mutex<Channel<V>>> as LockApi<Channel<V>>>::write(&self.channel)
+        {
+            let channel = lock.get_mut();
+            channel.closed = true;
+            channel.queue.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, vec};
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::testing::Value;
+
+    type Map = BTreeMap<String, Value>;
+
+    #[test]
+    fn callbacks_see_changes_until_unsubscribed() {
+        let mut state = Observable::<Map, Value>::new(Map::default());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let subscription = state
+            .observer()
+            .subscribe({
+                let seen = seen.clone();
+                move |change| seen.lock().unwrap().push(change.clone())
+            })
+            .unwrap();
+
+        state.set("count", Value::Int(1)).unwrap();
+        state.set("count", Value::Int(2)).unwrap();
+        assert!(state.observer().unsubscribe(subscription).unwrap());
+        state.set("count", Value::Int(3)).unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                StateChange {
+                    name: "count".into(),
+                    old: None,
+                    new: Value::Int(1),
+                },
+                StateChange {
+                    name: "count".into(),
+                    old: Some(Value::Int(1)),
+                    new: Value::Int(2),
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "async")]
+    mod stream {
+        use futures::{executor::block_on_stream, StreamExt};
+
+        use super::*;
+
+        #[test]
+        fn ends_when_the_state_is_dropped() {
+            let mut state = Observable::<Map, Value>::new(Map::default());
+            let observer = state.observer();
+            let stream = observer.stream().unwrap();
+
+            state.set("count", Value::Int(1)).unwrap();
+            drop(state);
+
+            let changes = block_on_stream(stream)
+                .map(|change| change.new)
+                .collect::<Vec<_>>();
+            assert_eq!(changes, [Value::Int(1)]);
+
+            // Streams opened afterwards end right away
+            let mut stream = observer.stream().unwrap();
+            assert_eq!(futures::executor::block_on(stream.next()), None);
+        }
+
+        #[test]
+        fn drops_the_oldest_changes_beyond_capacity() {
+            let mut state = Observable::<Map, Value>::new(Map::default());
+            let stream = state.observer().stream_with_capacity(2).unwrap();
+
+            for idx in 0..5 {
+                state.set("count", Value::Int(idx)).unwrap();
+            }
+            drop(state);
+
+            let changes = block_on_stream(stream)
+                .map(|change| change.new)
+                .collect::<Vec<_>>();
+            assert_eq!(changes, [Value::Int(3), Value::Int(4)]);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use super::State;
+use crate::{Error, Value};
+
+/// Values which can hold named children, like maps or objects
+pub trait NestedValue: Value + Sized {
+    fn get_field(&self, name: &str) -> Option<&Self>;
+
+    fn get_field_mut(&mut self, name: &str) -> Option<&mut Self>;
+
+    /// Insert or replace a child. Gives the value back if `self` cannot hold children
+    fn set_field(&mut self, name: &str, value: Self) -> Result<(), Self>;
+}
+
+/// A state where names are paths into nested values.
+///
+/// The first segment of the path is looked up in the wrapped state,
+/// the remaining segments are resolved with `NestedValue`.
+/// `get("user.address.city")` is the same as `get("user")` followed by
+/// walking the `address` and `city` fields.
+///
+/// Setting a nested path reads the root value out of the wrapped state,
+/// updates the copy and writes the whole root back, so it costs a clone of the root
+#[derive(Debug, Clone)]
+pub struct PathState<T> {
+    state: T,
+    separator: char,
+}
+
+impl<T> PathState<T> {
+    pub fn new(state: T) -> PathState<T> {
+        PathState {
+            state,
+            separator: '.',
+        }
+    }
+
+    pub fn with_separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.state
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.state
+    }
+
+    pub fn into_inner(self) -> T {
+        self.state
+    }
+}
+
+impl<T: Default> Default for PathState<T> {
+    fn default() -> Self {
+        PathState::new(T::default())
+    }
+}
+
+impl<T, V> State<V> for PathState<T>
+where
+    T: State<V>,
+    V: NestedValue + Clone,
+{
+    fn get(&self, name: &str) -> Result<Option<V>, Error<V>> {
+        let mut parts = name.split(self.separator);
+        let root = parts.next().unwrap_or(name);
+
+        let Some(root) = self.state.get(root)? else {
+            return Ok(None);
+        };
+
+        let mut current = &root;
+        for part in parts {
+            current = match current.get_field(part) {
+                Some(next) => next,
+                None => return Ok(None),
+            };
+        }
+
+        Ok(Some(current.clone()))
+    }
+
+    fn set(&mut self, name: &str, value: V) -> Result<(), Error<V>> {
+        let Some((parent, field)) = name.rsplit_once(self.separator) else {
+            return self.state.set(name, value);
+        };
+
+        let mut parts = parent.split(self.separator);
+        let root_name = parts.next().unwrap_or(parent);
+
+        let Some(mut root) = self.state.get(root_name)? else {
+            return Err(Error::UnknownField(name.into()));
+        };
+
+        let mut current = &mut root;
+        for part in parts {
+            current = match current.get_field_mut(part) {
+                Some(next) => next,
+                None => return Err(Error::UnknownField(name.into())),
+            };
+        }
+
+        current
+            .set_field(field, value)
+            .map_err(|_| Error::UnknownField(name.into()))?;
+
+        self.state.set(root_name, root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        collections::BTreeMap,
+        string::{String, ToString},
+    };
+
+    use super::*;
+    use crate::testing::Value;
+
+    fn map<const N: usize>(fields: [(&str, Value); N]) -> Value {
+        Value::Map(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn state() -> PathState<BTreeMap<String, Value>> {
+        let mut state = BTreeMap::new();
+        state.insert(
+            "user".to_string(),
+            map([
+                ("name", "Rasmus".into()),
+                ("address", map([("city", "Aarhus".into())])),
+            ]),
+        );
+        PathState::new(state)
+    }
+
+    #[test]
+    fn gets_nested_fields() {
+        let state = state();
+        assert_eq!(
+            state.get("user.address.city").unwrap(),
+            Some("Aarhus".into())
+        );
+        assert_eq!(state.get("user.name").unwrap(), Some("Rasmus".into()));
+    }
+
+    #[test]
+    fn missing_segments_are_none() {
+        let state = state();
+        assert_eq!(state.get("user.phone.number").unwrap(), None);
+        assert_eq!(state.get("nobody.address").unwrap(), None);
+        assert_eq!(state.get("user.name.first").unwrap(), None);
+    }
+
+    #[test]
+    fn sets_nested_fields() {
+        let mut state = state();
+        state.set("user.address.city", "Odense".into()).unwrap();
+        state.set("user.address.zip", "5000".into()).unwrap();
+
+        assert_eq!(
+            state.get("user.address.city").unwrap(),
+            Some("Odense".into())
+        );
+        assert_eq!(
+            state.get_ref()["user"],
+            map([
+                ("name", "Rasmus".into()),
+                (
+                    "address",
+                    map([("city", "Odense".into()), ("zip", "5000".into())])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn set_under_a_non_container_is_an_unknown_field() {
+        let mut state = state();
+        let err = state.set("user.name.first", "Rasmus".into()).unwrap_err();
+        assert!(matches!(err, Error::UnknownField(name) if name == "user.name.first"));
+
+        let err = state.set("nobody.name", "Rasmus".into()).unwrap_err();
+        assert!(matches!(err, Error::UnknownField(name) if name == "nobody.name"));
+    }
+
+    #[test]
+    fn paths_use_the_separator() {
+        let mut state = state().with_separator('/');
+        assert_eq!(
+            state.get("user/address/city").unwrap(),
+            Some("Aarhus".into())
+        );
+        assert_eq!(state.get("user.address.city").unwrap(), None);
+
+        state.set("user/address/city", "Odense".into()).unwrap();
+        assert_eq!(
+            state.get("user/address/city").unwrap(),
+            Some("Odense".into())
+        );
+    }
+}
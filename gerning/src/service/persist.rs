@@ -0,0 +1,349 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Error, Value};
+
+#[cfg(feature = "std")]
+use super::State;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Json,
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Json(serde_json::Error),
+    Binary(postcard::Error),
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Json(err) => write!(f, "json: {err}"),
+            SnapshotError::Binary(err) => write!(f, "binary: {err}"),
+            #[cfg(feature = "std")]
+            SnapshotError::Io(err) => write!(f, "io: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for SnapshotError {
+    // The format errors only implement the error trait with their std features on
+    #[cfg(feature = "std")]
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            SnapshotError::Json(err) => Some(err),
+            SnapshotError::Binary(err) => Some(err),
+            #[cfg(feature = "std")]
+            SnapshotError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(value: serde_json::Error) -> Self {
+        SnapshotError::Json(value)
+    }
+}
+
+impl From<postcard::Error> for SnapshotError {
+    fn from(value: postcard::Error) -> Self {
+        SnapshotError::Binary(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for SnapshotError {
+    fn from(value: std::io::Error) -> Self {
+        SnapshotError::Io(value)
+    }
+}
+
+impl<V: Value> From<SnapshotError> for Error<V> {
+    fn from(value: SnapshotError) -> Self {
+        Error::Runtime(Box::new(value))
+    }
+}
+
+/// Serialize a state to bytes and back again
+pub trait Snapshot: Sized {
+    fn snapshot(&self, format: Format) -> Result<Vec<u8>, SnapshotError>;
+
+    fn restore(bytes: &[u8], format: Format) -> Result<Self, SnapshotError>;
+}
+
+impl<T> Snapshot for T
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn snapshot(&self, format: Format) -> Result<Vec<u8>, SnapshotError> {
+        match format {
+            Format::Json => Ok(serde_json::to_vec(self)?),
+            Format::Binary => Ok(postcard::to_allocvec(self)?),
+        }
+    }
+
+    fn restore(bytes: &[u8], format: Format) -> Result<Self, SnapshotError> {
+        match format {
+            Format::Json => Ok(serde_json::from_slice(bytes)?),
+            Format::Binary => Ok(postcard::from_bytes(bytes)?),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Persist {
+    /// Write to disk after every successful `State::set`,
+    /// and when a guard from `FileState::get_mut` that was written through is dropped
+    #[default]
+    OnChange,
+    /// Only write to disk when `FileState::save` is called
+    Manual,
+}
+
+/// A state backed by a file on disk
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct FileState<T> {
+    state: T,
+    path: PathBuf,
+    format: Format,
+    persist: Persist,
+}
+
+#[cfg(feature = "std")]
+impl<T: Snapshot> FileState<T> {
+    pub fn new(state: T, path: impl Into<PathBuf>, format: Format) -> FileState<T> {
+        FileState {
+            state,
+            path: path.into(),
+            format,
+            persist: Persist::default(),
+        }
+    }
+
+    /// Load the state from `path`, falling back to `T::default()`
+    /// if the file does not exist yet
+    pub fn open(path: impl Into<PathBuf>, format: Format) -> Result<FileState<T>, SnapshotError>
+    where
+        T: Default,
+    {
+        let path = path.into();
+        let state = match std::fs::read(&path) {
+            Ok(bytes) => T::restore(&bytes, format)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(FileState {
+            state,
+            path,
+            format,
+            persist: Persist::default(),
+        })
+    }
+
+    pub fn with_persist(mut self, persist: Persist) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    pub fn save(&self) -> Result<(), SnapshotError> {
+        let bytes = self.state.snapshot(self.format)?;
+        let tmp = tmp_path(&self.path);
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub fn reload(&mut self) -> Result<(), SnapshotError> {
+        let bytes = std::fs::read(&self.path)?;
+        self.state = T::restore(&bytes, self.format)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.state
+    }
+
+    /// Mutable access to the state. Methods get the `FileState` itself,
+    /// so this is where their changes are picked up and persisted
+    pub fn get_mut(&mut self) -> FileStateMut<'_, T> {
+        FileStateMut {
+            file: self,
+            dirty: false,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.state
+    }
+}
+
+/// Writes the state to disk when dropped, if it was written through and the file
+/// persists on change. Errors are lost on drop, call `commit` to handle them
+#[cfg(feature = "std")]
+pub struct FileStateMut<'a, T: Snapshot> {
+    file: &'a mut FileState<T>,
+    dirty: bool,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Snapshot> FileStateMut<'a, T> {
+    /// Write the changes to disk now, regardless of the persist mode
+    pub fn commit(mut self) -> Result<(), SnapshotError> {
+        self.dirty = false;
+        self.file.save()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Snapshot> core::ops::Deref for FileStateMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.file.state
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Snapshot> core::ops::DerefMut for FileStateMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        &mut self.file.state
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Snapshot> Drop for FileStateMut<'a, T> {
+    fn drop(&mut self) {
+        if self.dirty && self.file.persist == Persist::OnChange {
+            let _ = self.file.save();
+        }
+    }
+}
+
+/// `a.json` is written through `a.json.tmp`, so files differing
+/// only in their extension don't share a temporary file
+#[cfg(feature = "std")]
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
+
+#[cfg(feature = "std")]
+impl<T, V> State<V> for FileState<T>
+where
+    T: State<V> + Snapshot,
+    V: Value,
+{
+    fn set(&mut self, name: &str, value: V) -> Result<(), Error<V>> {
+        self.state.set(name, value)?;
+        if self.persist == Persist::OnChange {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Option<V>, Error<V>> {
+        self.state.get(name)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::{collections::BTreeMap, string::String};
+
+    use super::*;
+    use crate::{
+        arguments::Arguments,
+        service::{DynService, Service, SyncState},
+        testing::Value,
+    };
+
+    type Map = BTreeMap<String, Value>;
+
+    fn path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(std::format!(
+            "gerning-persist-{}-{name}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn set_persists() {
+        let path = path("set.json");
+        let mut state = FileState::<Map>::open(&path, Format::Json).unwrap();
+        state.set("count", Value::Int(1)).unwrap();
+
+        let state = FileState::<Map>::open(&path, Format::Json).unwrap();
+        assert_eq!(state.get("count").unwrap(), Some(Value::Int(1)));
+    }
+
+    #[test]
+    fn method_calls_persist() {
+        let path = path("call.bin");
+        let state = FileState::<Map>::open(&path, Format::Binary).unwrap();
+        let mut service = DynService::new(SyncState::new(state));
+        service.register(
+            "bump",
+            |this: &mut FileState<Map>, _ctx: &mut (), _args: Arguments<Value>| {
+                this.get_mut().insert("count".into(), Value::Int(2));
+                Ok::<_, Error<Value>>(())
+            },
+        );
+
+        service.call(&mut (), "bump", Arguments::default()).unwrap();
+
+        let state = FileState::<Map>::open(&path, Format::Binary).unwrap();
+        assert_eq!(state.get("count").unwrap(), Some(Value::Int(2)));
+    }
+
+    #[test]
+    fn manual_waits_for_save() {
+        let path = path("manual.json");
+        let mut state = FileState::<Map>::open(&path, Format::Json)
+            .unwrap()
+            .with_persist(Persist::Manual);
+        state.get_mut().insert("count".into(), Value::Int(3));
+        assert!(!path.exists());
+
+        state.save().unwrap();
+        let state = FileState::<Map>::open(&path, Format::Json).unwrap();
+        assert_eq!(state.get("count").unwrap(), Some(Value::Int(3)));
+    }
+
+    #[test]
+    fn reads_do_not_persist() {
+        let path = path("read.json");
+        let mut state = FileState::<Map>::open(&path, Format::Json).unwrap();
+        assert!(state.get_mut().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn tmp_keeps_the_extension() {
+        assert_eq!(tmp_path(Path::new("a.json")), PathBuf::from("a.json.tmp"));
+        assert_eq!(tmp_path(Path::new("a.bin")), PathBuf::from("a.bin.tmp"));
+    }
+}
@@ -3,32 +3,124 @@ use hashbrown::HashMap;
 
 use crate::{arguments::Arguments, signature::Signature, Error, Value};
 #[cfg(feature = "async")]
-use core::future::Future;
+use crate::{cancel::CancellationToken, time::Timer};
+#[cfg(feature = "async")]
+use core::{future::Future, time::Duration};
 
-#[derive(Clone)]
+/// The signatures of the methods of a service.
+/// Cloning only bumps a reference count
 pub struct ServiceSignature<T: Value> {
-    services: Arc<HashMap<String, Signature<T>>>,
+    services: Arc<HashMap<String, Arc<Signature<T>>>>,
+}
+
+impl<T: Value> Clone for ServiceSignature<T> {
+    fn clone(&self) -> Self {
+        ServiceSignature {
+            services: self.services.clone(),
+        }
+    }
+}
+
+impl<T: Value> Default for ServiceSignature<T> {
+    fn default() -> Self {
+        ServiceSignature {
+            services: Arc::default(),
+        }
+    }
 }
 
 impl<T: Value> From<HashMap<String, Signature<T>>> for ServiceSignature<T> {
     fn from(value: HashMap<String, Signature<T>>) -> Self {
         Self {
-            services: Arc::new(value),
+            services: Arc::new(
+                value
+                    .into_iter()
+                    .map(|(name, signature)| (name, Arc::new(signature)))
+                    .collect(),
+            ),
         }
     }
 }
 
 impl<T: Value> ServiceSignature<T> {
-    pub fn iter(&self) -> hashbrown::hash_map::Iter<'_, String, Signature<T>> {
+    pub fn iter(&self) -> hashbrown::hash_map::Iter<'_, String, Arc<Signature<T>>> {
         self.services.iter()
     }
 
-    pub fn functions(&self) -> hashbrown::hash_map::Keys<'_, String, Signature<T>> {
+    pub fn functions(&self) -> hashbrown::hash_map::Keys<'_, String, Arc<Signature<T>>> {
         self.services.keys()
     }
 
     pub fn get(&self, name: &str) -> Option<&Signature<T>> {
-        self.services.get(name)
+        self.services.get(name).map(|signature| &**signature)
+    }
+
+    /// Add or replace the signature of a method.
+    /// Copies the table if it is shared, but never the signatures themselves
+    pub(crate) fn insert(&mut self, name: &str, signature: Signature<T>) {
+        Arc::make_mut(&mut self.services).insert(name.into(), Arc::new(signature));
+    }
+}
+
+/// A handle to a method, from `resolve`, for calling it again without looking it up by name.
+///
+/// A handle is only valid for the service that resolved it. Registering the method again
+/// invalidates its handles: calls through them fail with `Error::StaleMethod`,
+/// and the name has to be resolved again
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodId {
+    index: u32,
+    generation: u32,
+    name: Arc<str>,
+}
+
+impl MethodId {
+    pub(crate) fn new(index: u32, generation: u32, name: Arc<str>) -> MethodId {
+        MethodId {
+            index,
+            generation,
+            name,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Bumped every time the method is registered again
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// How a call names its method, for wrappers passing calls on
+#[cfg(feature = "async")]
+#[derive(Clone, Copy)]
+pub(crate) enum MethodRef<'a> {
+    Name(&'a str),
+    Id(&'a MethodId),
+}
+
+#[cfg(feature = "async")]
+impl<'a> MethodRef<'a> {
+    pub(crate) fn call<S, C, V>(
+        self,
+        service: &'a S,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> S::Call<'a>
+    where
+        S: AsyncService<C, V>,
+        V: Value,
+    {
+        match self {
+            MethodRef::Name(name) => service.call(ctx, name, args),
+            MethodRef::Id(id) => service.call_by_id(ctx, id, args),
+        }
     }
 }
 
@@ -37,6 +129,17 @@ pub trait Service<C, V: Value> {
     // fn set_value(&self, name: &str, value: V) -> Result<(), Error<V>>;
     // fn get_value(&self, name: &str) -> Result<Option<V>, Error<V>>;
     fn call(&self, ctx: &mut C, name: &str, args: Arguments<V>) -> Result<V, Error<V>>;
+
+    /// A handle to the method `name`, if the service has it and supports handles
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        let _ = name;
+        None
+    }
+
+    /// Call the method behind a handle. Falls back to calling it by name
+    fn call_by_id(&self, ctx: &mut C, id: &MethodId, args: Arguments<V>) -> Result<V, Error<V>> {
+        self.call(ctx, id.name(), args)
+    }
 }
 
 #[cfg(feature = "async")]
@@ -57,6 +160,22 @@ pub trait AsyncService<C, V: Value> {
     // fn set_value<'a>(&'a self, name: &'a str, value: V) -> Self::Set<'a>;
     // fn get_value<'a>(&'a self, name: &'a str) -> Self::Get<'a>;
     fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a>;
+
+    /// A handle to the method `name`, if the service has it and supports handles
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        let _ = name;
+        None
+    }
+
+    /// Call the method behind a handle. Falls back to calling it by name
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        self.call(ctx, id.name(), args)
+    }
 }
 
 #[cfg(feature = "async")]
@@ -72,6 +191,54 @@ pub trait AsyncServiceExt<C, V: Value>: AsyncService<C, V> {
     {
         super::box_service(self)
     }
+
+    fn timeout<T>(self, duration: Duration) -> super::TimeoutService<Self, T>
+    where
+        Self: Sized,
+        T: Timer,
+    {
+        super::TimeoutService::new(self, duration)
+    }
+
+    fn with_cancellation(self, token: CancellationToken) -> super::CancellableService<Self>
+    where
+        Self: Sized,
+    {
+        super::CancellableService::new(self, token)
+    }
+
+    /// Start a batch of concurrent calls against this service
+    fn batch(&self) -> super::Batch<'_, Self, C, V>
+    where
+        Self: Sized,
+    {
+        super::Batch::new(self)
+    }
+
+    /// Serve the service from a context shared behind an async `Mutex`
+    fn locked(self) -> super::LockedService<Self>
+    where
+        Self: Sized,
+    {
+        super::LockedService::new(self)
+    }
+
+    fn concurrency_limit<T>(self) -> super::ConcurrencyLimit<Self, T>
+    where
+        Self: Sized,
+        T: Timer,
+    {
+        super::ConcurrencyLimit::new(self)
+    }
+
+    #[cfg(feature = "std")]
+    fn rate_limit<T>(self) -> super::RateLimit<Self, T>
+    where
+        Self: Sized,
+        T: Timer,
+    {
+        super::RateLimit::new(self)
+    }
 }
 
 #[cfg(feature = "async")]
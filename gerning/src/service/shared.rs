@@ -0,0 +1,294 @@
+use alloc::boxed::Box;
+
+use super::{MethodId, Service, ServiceSignature};
+use crate::{
+    arguments::Arguments,
+    signature::{Parameters, Signature},
+    Error, Typed, Value,
+};
+
+#[cfg(feature = "async")]
+use super::service::MethodRef;
+#[cfg(feature = "async")]
+use super::AsyncService;
+#[cfg(feature = "async")]
+use crate::shared::{LockedCall, LockedFuture};
+#[cfg(feature = "async")]
+use core::future::Future;
+
+/// Like `Service`, but the context is only borrowed shared,
+/// so one context can serve many concurrent calls
+pub trait SharedService<C, V: Value> {
+    fn signature(&self) -> ServiceSignature<V>;
+    fn call(&self, ctx: &C, name: &str, args: Arguments<V>) -> Result<V, Error<V>>;
+
+    /// A handle to the method `name`, if the service has it and supports handles
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        let _ = name;
+        None
+    }
+
+    /// Call the method behind a handle. Falls back to calling it by name
+    fn call_by_id(&self, ctx: &C, id: &MethodId, args: Arguments<V>) -> Result<V, Error<V>> {
+        self.call(ctx, id.name(), args)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncSharedService<C, V: Value> {
+    type Call<'a>: Future<Output = Result<V, Error<V>>>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> ServiceSignature<V>;
+
+    fn call<'a>(&'a self, ctx: &'a C, name: &'a str, args: Arguments<V>) -> Self::Call<'a>;
+
+    /// A handle to the method `name`, if the service has it and supports handles
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        let _ = name;
+        None
+    }
+
+    /// Call the method behind a handle. Falls back to calling it by name
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        self.call(ctx, id.name(), args)
+    }
+}
+
+pub trait SharedMethodCallable<S, C, V: Value> {
+    fn signature(&self) -> Signature<V>;
+    fn call(&self, this: &mut S, ctx: &C, args: Arguments<V>) -> Result<V, Error<V>>;
+}
+
+impl<S, C, V: Value> SharedMethodCallable<S, C, V>
+    for Box<dyn SharedMethodCallable<S, C, V> + Send + Sync>
+{
+    fn signature(&self) -> Signature<V> {
+        (**self).signature()
+    }
+
+    fn call(&self, this: &mut S, ctx: &C, args: Arguments<V>) -> Result<V, Error<V>> {
+        (**self).call(this, ctx, args)
+    }
+}
+
+impl<F, S, C, U, E, V: Value> SharedMethodCallable<S, C, V> for F
+where
+    F: Fn(&mut S, &C, Arguments<V>) -> Result<U, E>,
+    E: Into<Error<V>>,
+    U: Into<V> + Typed<V>,
+{
+    fn signature(&self) -> Signature<V> {
+        Signature::new(Parameters::new(), U::get_type())
+    }
+
+    fn call(&self, this: &mut S, ctx: &C, args: Arguments<V>) -> Result<V, Error<V>> {
+        (self)(this, ctx, args)
+            .map(|m| m.into())
+            .map_err(|e| e.into())
+    }
+}
+
+/// Runs a shared-context service where an exclusive context is expected
+pub struct ExclusiveService<S> {
+    service: S,
+}
+
+impl<S> ExclusiveService<S> {
+    pub fn new(service: S) -> ExclusiveService<S> {
+        ExclusiveService { service }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S, C, V> Service<C, V> for ExclusiveService<S>
+where
+    S: SharedService<C, V>,
+    V: Value,
+{
+    fn signature(&self) -> ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call(&self, ctx: &mut C, name: &str, args: Arguments<V>) -> Result<V, Error<V>> {
+        self.service.call(ctx, name, args)
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id(&self, ctx: &mut C, id: &MethodId, args: Arguments<V>) -> Result<V, Error<V>> {
+        self.service.call_by_id(ctx, id, args)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S, C, V> AsyncService<C, V> for ExclusiveService<S>
+where
+    S: AsyncSharedService<C, V>,
+    V: Value,
+{
+    type Call<'a>
+        = S::Call<'a>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
+        self.service.call(ctx, name, args)
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        self.service.call_by_id(ctx, id, args)
+    }
+}
+
+/// Serves an exclusive-context service from a shared context by locking it for each call.
+/// Implements `SharedService<std::sync::Mutex<C>, V>` and `AsyncSharedService<async_lock::Mutex<C>, V>`
+pub struct LockedService<S> {
+    service: S,
+}
+
+impl<S> LockedService<S> {
+    pub fn new(service: S) -> LockedService<S> {
+        LockedService { service }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, C, V> SharedService<std::sync::Mutex<C>, V> for LockedService<S>
+where
+    S: Service<C, V>,
+    V: Value,
+{
+    fn signature(&self) -> ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call(
+        &self,
+        ctx: &std::sync::Mutex<C>,
+        name: &str,
+        args: Arguments<V>,
+    ) -> Result<V, Error<V>> {
+        let mut lock = ctx.lock().unwrap_or_else(|err| err.into_inner());
+        self.service.call(&mut lock, name, args)
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id(
+        &self,
+        ctx: &std::sync::Mutex<C>,
+        id: &MethodId,
+        args: Arguments<V>,
+    ) -> Result<V, Error<V>> {
+        let mut lock = ctx.lock().unwrap_or_else(|err| err.into_inner());
+        self.service.call_by_id(&mut lock, id, args)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S, C, V> AsyncSharedService<async_lock::Mutex<C>, V> for LockedService<S>
+where
+    S: AsyncService<C, V>,
+    V: Value,
+{
+    type Call<'a>
+        = LockedFuture<'a, C, CallMethod<'a, S, V>>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call<'a>(
+        &'a self,
+        ctx: &'a async_lock::Mutex<C>,
+        name: &'a str,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        LockedFuture::new(
+            ctx,
+            CallMethod {
+                service: &self.service,
+                method: MethodRef::Name(name),
+                args,
+            },
+        )
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a async_lock::Mutex<C>,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        LockedFuture::new(
+            ctx,
+            CallMethod {
+                service: &self.service,
+                method: MethodRef::Id(id),
+                args,
+            },
+        )
+    }
+}
+
+/// Calls an async service with the locked context
+#[cfg(feature = "async")]
+pub struct CallMethod<'a, S, V: Value> {
+    service: &'a S,
+    method: MethodRef<'a>,
+    args: Arguments<V>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, S, C, V> LockedCall<'a, C> for CallMethod<'a, S, V>
+where
+    S: AsyncService<C, V>,
+    C: 'a,
+    V: Value,
+{
+    type Future = S::Call<'a>;
+
+    fn call(self, ctx: &'a mut C) -> Self::Future {
+        self.method.call(self.service, ctx, self.args)
+    }
+}
@@ -4,9 +4,10 @@ use alloc::{
     rc::Rc,
     string::{String, ToString},
     sync::Arc,
+    vec::Vec,
 };
 use avagarden::sync::Mutex;
-use core::cell::RefCell;
+use core::{cell::RefCell, hash::BuildHasher};
 #[cfg(feature = "async")]
 use futures_core::Future;
 #[cfg(feature = "async")]
@@ -40,6 +41,8 @@ pub trait AsyncStateType<V: Value>: HasState {
 }
 
 pub struct SendState<T> {
+    // Only read by the async state impls
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
     state: Arc<Mutex<T>>,
 }
 
@@ -140,6 +143,18 @@ pub trait State<V: Value> {
     fn get(&self, name: &str) -> Result<Option<V>, Error<V>>;
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateField<V: Value> {
+    pub name: &'static str,
+    pub ty: V::Type,
+}
+
+/// Introspection of states with a fixed set of fields.
+/// Implemented by `#[derive(State)]`
+pub trait StateFields<V: Value> {
+    fn fields() -> Vec<StateField<V>>;
+}
+
 impl<V: Value + Clone> State<V> for BTreeMap<String, V> {
     fn get(&self, name: &str) -> Result<Option<V>, Error<V>> {
         Ok(self.get(name).cloned())
@@ -150,3 +165,26 @@ impl<V: Value + Clone> State<V> for BTreeMap<String, V> {
         Ok(())
     }
 }
+
+impl<V: Value + Clone, S: BuildHasher> State<V> for hashbrown::HashMap<String, V, S> {
+    fn get(&self, name: &str) -> Result<Option<V>, Error<V>> {
+        Ok(self.get(name).cloned())
+    }
+
+    fn set(&mut self, name: &str, value: V) -> Result<(), Error<V>> {
+        self.insert(name.to_string(), value);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: Value + Clone, S: BuildHasher> State<V> for std::collections::HashMap<String, V, S> {
+    fn get(&self, name: &str) -> Result<Option<V>, Error<V>> {
+        Ok(self.get(name).cloned())
+    }
+
+    fn set(&mut self, name: &str, value: V) -> Result<(), Error<V>> {
+        self.insert(name.to_string(), value);
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use alloc::boxed::Box;
+use futures_core::{
+    stream::{BoxStream, LocalBoxStream},
+    Stream,
+};
+
+use crate::{
+    arguments::Arguments,
+    signature::{Parameters, Signature},
+    Error, FuncStream, Resultable, Typed, Value,
+};
+
+/// A method producing many results over time.
+/// The state is only borrowed while the stream is opened, so the stream cannot hold on to it
+pub trait StreamMethodCallable<S, C, V: Value> {
+    type Stream<'a>: Stream<Item = Result<V, Error<V>>>
+    where
+        Self: 'a,
+        C: 'a;
+    fn signature(&self) -> Signature<V>;
+
+    fn call_stream<'a>(
+        &'a self,
+        this: &mut S,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> Self::Stream<'a>;
+}
+
+pub trait StreamMethodCallableExt<S, C, V: Value>: StreamMethodCallable<S, C, V> {
+    fn boxed(self) -> BoxStreamMethodCallable<'static, S, C, V>
+    where
+        Self: Sized + 'static + Send + Sync,
+        for<'a> Self::Stream<'a>: Send,
+        S: 'static,
+        V: 'static,
+        for<'a> C: 'a,
+    {
+        Box::new(self)
+    }
+
+    fn boxed_local(self) -> LocalBoxStreamMethodCallable<'static, S, C, V>
+    where
+        Self: Sized + 'static + Send + Sync,
+        S: 'static,
+        V: 'static,
+        C: 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<T, S, C, V: Value> StreamMethodCallableExt<S, C, V> for T where T: StreamMethodCallable<S, C, V>
+{}
+
+pub type BoxStreamMethodCallable<'a, S, C, V> =
+    Box<dyn internal::BoxStreamCall<S, C, V> + Send + Sync + 'a>;
+
+pub type LocalBoxStreamMethodCallable<'a, S, C, V> =
+    Box<dyn internal::BoxLocalStreamCall<S, C, V> + Send + Sync + 'a>;
+
+mod internal {
+    use super::*;
+
+    pub trait BoxStreamCall<S, C, V: Value> {
+        fn signature(&self) -> Signature<V>;
+        fn call<'a>(
+            &'a self,
+            this: &mut S,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> BoxStream<'a, Result<V, Error<V>>>;
+    }
+
+    impl<T, S, C, V> BoxStreamCall<S, C, V> for T
+    where
+        T: StreamMethodCallable<S, C, V>,
+        for<'a> T::Stream<'a>: Send,
+        S: 'static,
+        V: Value + 'static,
+        C: 'static,
+    {
+        fn signature(&self) -> Signature<V> {
+            <T as StreamMethodCallable<S, C, V>>::signature(self)
+        }
+
+        fn call<'a>(
+            &'a self,
+            this: &mut S,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> BoxStream<'a, Result<V, Error<V>>> {
+            Box::pin(<T as StreamMethodCallable<S, C, V>>::call_stream(
+                self, this, ctx, args,
+            ))
+        }
+    }
+
+    pub trait BoxLocalStreamCall<S, C, V: Value> {
+        fn signature(&self) -> Signature<V>;
+        fn call<'a>(
+            &'a self,
+            this: &mut S,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> LocalBoxStream<'a, Result<V, Error<V>>>;
+    }
+
+    impl<T, S, C, V> BoxLocalStreamCall<S, C, V> for T
+    where
+        T: StreamMethodCallable<S, C, V>,
+        S: 'static,
+        V: Value + 'static,
+        C: 'static,
+    {
+        fn signature(&self) -> Signature<V> {
+            <T as StreamMethodCallable<S, C, V>>::signature(self)
+        }
+
+        fn call<'a>(
+            &'a self,
+            this: &mut S,
+            ctx: &'a mut C,
+            args: Arguments<V>,
+        ) -> LocalBoxStream<'a, Result<V, Error<V>>> {
+            Box::pin(<T as StreamMethodCallable<S, C, V>>::call_stream(
+                self, this, ctx, args,
+            ))
+        }
+    }
+}
+
+impl<S: 'static, C, V: Value + 'static> StreamMethodCallable<S, C, V>
+    for BoxStreamMethodCallable<'static, S, C, V>
+{
+    type Stream<'a>
+        = BoxStream<'a, Result<V, Error<V>>>
+    where
+        C: 'a;
+    fn signature(&self) -> Signature<V> {
+        (**self).signature()
+    }
+    fn call_stream<'a>(
+        &'a self,
+        this: &mut S,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> Self::Stream<'a> {
+        (**self).call(this, ctx, args)
+    }
+}
+
+impl<S: 'static, C, V: Value + 'static> StreamMethodCallable<S, C, V>
+    for LocalBoxStreamMethodCallable<'static, S, C, V>
+{
+    type Stream<'a>
+        = LocalBoxStream<'a, Result<V, Error<V>>>
+    where
+        C: 'a;
+    fn signature(&self) -> Signature<V> {
+        (**self).signature()
+    }
+    fn call_stream<'a>(
+        &'a self,
+        this: &mut S,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> Self::Stream<'a> {
+        (**self).call(this, ctx, args)
+    }
+}
+
+impl<F, S, U, C, V: Value> StreamMethodCallable<S, C, V> for F
+where
+    F: Fn(&mut S, &mut C, Arguments<V>) -> U,
+    U: Stream,
+    U::Item: Resultable,
+    <U::Item as Resultable>::Error: Into<Error<V>>,
+    <U::Item as Resultable>::Ok: Into<V> + Typed<V>,
+{
+    type Stream<'a>
+        = FuncStream<U, V>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        Signature::stream(
+            Parameters::new(),
+            <<U::Item as Resultable>::Ok as Typed<V>>::get_type(),
+        )
+    }
+
+    fn call_stream<'a>(
+        &'a self,
+        this: &mut S,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> Self::Stream<'a> {
+        FuncStream::new((self)(this, ctx, args))
+    }
+}
@@ -0,0 +1,254 @@
+use core::{marker::PhantomData, time::Duration};
+
+use super::{AsyncService, MethodId};
+use crate::{
+    arguments::Arguments,
+    cancel::{CancellableFuture, CancellationToken},
+    time::{TimeoutFuture, Timer},
+    Value,
+};
+
+/// Fails calls with `Error::Timeout` if they do not complete in time
+pub struct TimeoutService<S, T> {
+    service: S,
+    duration: Duration,
+    _timer: PhantomData<T>,
+}
+
+impl<S, T> TimeoutService<S, T> {
+    pub fn new(service: S, duration: Duration) -> TimeoutService<S, T> {
+        TimeoutService {
+            service,
+            duration,
+            _timer: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S, T, C, V> AsyncService<C, V> for TimeoutService<S, T>
+where
+    S: AsyncService<C, V>,
+    T: Timer,
+    V: Value,
+{
+    type Call<'a>
+        = TimeoutFuture<S::Call<'a>, T::Sleep>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> super::ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
+        TimeoutFuture::new(self.service.call(ctx, name, args), self.duration, T::sleep)
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        TimeoutFuture::new(
+            self.service.call_by_id(ctx, id, args),
+            self.duration,
+            T::sleep,
+        )
+    }
+}
+
+/// Aborts in-flight calls with `Error::Cancelled` when the token is cancelled
+pub struct CancellableService<S> {
+    service: S,
+    token: CancellationToken,
+}
+
+impl<S> CancellableService<S> {
+    pub fn new(service: S, token: CancellationToken) -> CancellableService<S> {
+        CancellableService { service, token }
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S, C, V> AsyncService<C, V> for CancellableService<S>
+where
+    S: AsyncService<C, V>,
+    V: Value,
+{
+    type Call<'a>
+        = CancellableFuture<S::Call<'a>>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> super::ServiceSignature<V> {
+        self.service.signature()
+    }
+
+    fn call<'a>(&'a self, ctx: &'a mut C, name: &'a str, args: Arguments<V>) -> Self::Call<'a> {
+        self.token.run(self.service.call(ctx, name, args))
+    }
+
+    fn resolve(&self, name: &str) -> Option<MethodId> {
+        self.service.resolve(name)
+    }
+
+    fn call_by_id<'a>(
+        &'a self,
+        ctx: &'a mut C,
+        id: &'a MethodId,
+        args: Arguments<V>,
+    ) -> Self::Call<'a> {
+        self.token.run(self.service.call_by_id(ctx, id, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use core::{
+        future::{pending, ready, Future, Pending, Ready},
+        pin::Pin,
+    };
+    use futures::{executor::block_on, future::join};
+
+    use super::*;
+    use crate::{service::ServiceSignature, testing::Value, Error};
+
+    /// `slow` never completes, every other method answers with its name
+    struct Calls;
+
+    impl Calls {
+        fn answer<'a>(
+            name: &str,
+        ) -> Pin<Box<dyn Future<Output = Result<Value, Error<Value>>> + 'a>> {
+            match name {
+                "slow" => Box::pin(pending()),
+                name => Box::pin(ready(Ok(name.into()))),
+            }
+        }
+    }
+
+    impl AsyncService<(), Value> for Calls {
+        type Call<'a> = Pin<Box<dyn Future<Output = Result<Value, Error<Value>>> + 'a>>;
+
+        fn signature(&self) -> ServiceSignature<Value> {
+            ServiceSignature::default()
+        }
+
+        fn call<'a>(
+            &'a self,
+            _ctx: &'a mut (),
+            name: &'a str,
+            _args: Arguments<Value>,
+        ) -> Self::Call<'a> {
+            Calls::answer(name)
+        }
+
+        fn resolve(&self, name: &str) -> Option<MethodId> {
+            Some(MethodId::new(0, 0, name.into()))
+        }
+
+        fn call_by_id<'a>(
+            &'a self,
+            _ctx: &'a mut (),
+            id: &'a MethodId,
+            _args: Arguments<Value>,
+        ) -> Self::Call<'a> {
+            Calls::answer(id.name())
+        }
+    }
+
+    /// Times out on the first poll
+    struct Expired;
+
+    impl Timer for Expired {
+        type Sleep = Ready<()>;
+
+        fn sleep(_duration: Duration) -> Self::Sleep {
+            ready(())
+        }
+    }
+
+    /// Never times out
+    struct Forever;
+
+    impl Timer for Forever {
+        type Sleep = Pending<()>;
+
+        fn sleep(_duration: Duration) -> Self::Sleep {
+            pending()
+        }
+    }
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn slow_methods_time_out() {
+        let service = TimeoutService::<_, Expired>::new(Calls, SECOND);
+        let ret = block_on(service.call(&mut (), "slow", Arguments::default()));
+        assert!(matches!(ret, Err(Error::Timeout)));
+
+        // A method which is done on the first poll beats the timer
+        let ret = block_on(service.call(&mut (), "fast", Arguments::default()));
+        assert_eq!(ret.unwrap(), Value::from("fast"));
+    }
+
+    #[test]
+    fn in_flight_calls_are_cancelled() {
+        let token = CancellationToken::new();
+        let service = CancellableService::new(Calls, token.clone());
+
+        let (ret, ()) = block_on(join(
+            service.call(&mut (), "slow", Arguments::default()),
+            async { token.cancel() },
+        ));
+        assert!(matches!(ret, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn handles_are_passed_through_both_wrappers() {
+        let token = CancellationToken::new();
+        let service = CancellableService::new(
+            TimeoutService::<_, Forever>::new(Calls, SECOND),
+            token.clone(),
+        );
+
+        let fast = service.resolve("fast").expect("a handle");
+        assert_eq!(fast.name(), "fast");
+        let ret = block_on(service.call_by_id(&mut (), &fast, Arguments::default()));
+        assert_eq!(ret.unwrap(), Value::from("fast"));
+
+        let slow = service.resolve("slow").expect("a handle");
+        let (ret, ()) = block_on(join(
+            service.call_by_id(&mut (), &slow, Arguments::default()),
+            async { token.cancel() },
+        ));
+        assert!(matches!(ret, Err(Error::Cancelled)));
+
+        let service = TimeoutService::<_, Expired>::new(
+            CancellableService::new(Calls, CancellationToken::new()),
+            SECOND,
+        );
+        let slow = service.resolve("slow").expect("a handle");
+        let ret = block_on(service.call_by_id(&mut (), &slow, Arguments::default()));
+        assert!(matches!(ret, Err(Error::Timeout)));
+    }
+}
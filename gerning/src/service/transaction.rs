@@ -0,0 +1,278 @@
+use crate::{arguments::Arguments, signature::Signature, Error, Value};
+
+use super::method::MethodCallable;
+
+#[cfg(feature = "async")]
+use super::method::AsyncMethodCallable;
+#[cfg(feature = "async")]
+use core::{future::Future, marker::PhantomData, pin::Pin, task::Poll};
+#[cfg(feature = "async")]
+use futures_core::ready;
+#[cfg(feature = "async")]
+use pin_project_lite::pin_project;
+
+/// Runs a method against a snapshot of the service state.
+/// If the method returns an error, or an async call is dropped before it
+/// completes, the state is restored to the value it had before the call.
+#[derive(Debug, Clone, Copy)]
+pub struct Transaction<M> {
+    method: M,
+}
+
+impl<M> Transaction<M> {
+    pub fn new(method: M) -> Transaction<M> {
+        Transaction { method }
+    }
+
+    pub fn into_inner(self) -> M {
+        self.method
+    }
+}
+
+impl<M, S, C, V> MethodCallable<S, C, V> for Transaction<M>
+where
+    M: MethodCallable<S, C, V>,
+    S: Clone,
+    V: Value,
+{
+    fn signature(&self) -> Signature<V> {
+        self.method.signature()
+    }
+
+    fn call(&self, this: &mut S, ctx: &mut C, args: Arguments<V>) -> Result<V, Error<V>> {
+        let snapshot = this.clone();
+        let ret = self.method.call(this, ctx, args);
+        if ret.is_err() {
+            *this = snapshot;
+        }
+        ret
+    }
+}
+
+#[cfg(feature = "async")]
+impl<M, S, C, V> AsyncMethodCallable<S, C, V> for Transaction<M>
+where
+    M: AsyncMethodCallable<S, C, V>,
+    S: Clone,
+    V: Value,
+{
+    type Future<'a>
+        = TransactionFuture<'a, M::Future<'a>, S>
+    where
+        Self: 'a,
+        C: 'a,
+        S: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.method.signature()
+    }
+
+    fn call_async<'a>(
+        &'a self,
+        this: &'a mut S,
+        ctx: &'a mut C,
+        args: Arguments<V>,
+    ) -> Self::Future<'a> {
+        let snapshot = this.clone();
+        let target = this as *mut S;
+        let future = self.method.call_async(unsafe { &mut *target }, ctx, args);
+
+        TransactionFuture {
+            state: TransactionFutureState::Call {
+                future,
+                snapshot: Some(snapshot),
+            },
+            target,
+            lifetime: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    #[project = TransactionProj]
+    enum TransactionFutureState<F, S> {
+        Call {
+            #[pin]
+            future: F,
+            snapshot: Option<S>,
+        },
+        Done,
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    /// Restores the snapshot if the call fails, or if the future is dropped before it completes
+    pub struct TransactionFuture<'a, F, S> {
+        #[pin]
+        state: TransactionFutureState<F, S>,
+        target: *mut S,
+        lifetime: PhantomData<&'a mut S>,
+    }
+
+    impl<'a, F, S> PinnedDrop for TransactionFuture<'a, F, S> {
+        fn drop(this: Pin<&mut Self>) {
+            let mut this = this.project();
+            let snapshot = match this.state.as_mut().project() {
+                TransactionProj::Call { snapshot, .. } => snapshot.take(),
+                TransactionProj::Done => None,
+            };
+
+            // Drop the inner future before touching the state again
+            this.state.set(TransactionFutureState::Done);
+
+            if let Some(snapshot) = snapshot {
+                unsafe { **this.target = snapshot };
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+unsafe impl<'a, F: Send, S: Send> Send for TransactionFuture<'a, F, S> {}
+
+#[cfg(feature = "async")]
+impl<'a, F, S, V> Future for TransactionFuture<'a, F, S>
+where
+    F: Future<Output = Result<V, Error<V>>>,
+    V: Value,
+{
+    type Output = Result<V, Error<V>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.as_mut().project();
+
+        let (ret, snapshot) = match this.state.as_mut().project() {
+            TransactionProj::Call { future, snapshot } => {
+                let ret = ready!(future.poll(cx));
+                (ret, snapshot.take())
+            }
+            TransactionProj::Done => panic!("poll after done"),
+        };
+
+        // Drop the inner future before touching the state again
+        this.state.set(TransactionFutureState::Done);
+
+        if ret.is_err() {
+            if let Some(snapshot) = snapshot {
+                unsafe { **this.target = snapshot };
+            }
+        }
+
+        Poll::Ready(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Value;
+
+    fn bump(this: &mut i64, _ctx: &mut (), _args: Arguments<Value>) -> Result<i64, Error<Value>> {
+        *this += 1;
+        Ok(*this)
+    }
+
+    fn bump_and_fail(
+        this: &mut i64,
+        _ctx: &mut (),
+        _args: Arguments<Value>,
+    ) -> Result<i64, Error<Value>> {
+        *this += 1;
+        Err(Error::MethodNotFound)
+    }
+
+    #[test]
+    fn commits_on_success() {
+        let mut state = 0i64;
+        let ret = Transaction::new(bump).call(&mut state, &mut (), Arguments::default());
+        assert_eq!(ret.unwrap(), Value::Int(1));
+        assert_eq!(state, 1);
+    }
+
+    #[test]
+    fn rolls_back_on_error() {
+        let mut state = 0i64;
+        let ret = Transaction::new(bump_and_fail).call(&mut state, &mut (), Arguments::default());
+        assert!(ret.is_err());
+        assert_eq!(state, 0);
+    }
+
+    #[cfg(feature = "async")]
+    mod r#async {
+        use alloc::boxed::Box;
+        use core::pin::pin;
+
+        use super::*;
+
+        /// Bumps the state, then waits for `release` before it returns `result`
+        struct Bump {
+            result: Result<i64, ()>,
+        }
+
+        impl AsyncMethodCallable<i64, bool, Value> for Bump {
+            type Future<'a> = Pin<Box<dyn Future<Output = Result<Value, Error<Value>>> + 'a>>;
+
+            fn signature(&self) -> Signature<Value> {
+                MethodCallable::<i64, (), Value>::signature(&bump)
+            }
+
+            fn call_async<'a>(
+                &'a self,
+                this: &'a mut i64,
+                release: &'a mut bool,
+                _args: Arguments<Value>,
+            ) -> Self::Future<'a> {
+                Box::pin(async move {
+                    *this += 1;
+                    core::future::poll_fn(|_| match release {
+                        true => Poll::Ready(()),
+                        false => Poll::Pending,
+                    })
+                    .await;
+                    match self.result {
+                        Ok(ret) => Ok(Value::Int(ret)),
+                        Err(()) => Err(Error::MethodNotFound),
+                    }
+                })
+            }
+        }
+
+        async fn call(method: &Transaction<Bump>, state: &mut i64, release: bool) -> Option<bool> {
+            let mut release = release;
+            let mut future = pin!(method.call_async(state, &mut release, Arguments::default()));
+            match futures::poll!(future.as_mut()) {
+                Poll::Ready(ret) => Some(ret.is_ok()),
+                Poll::Pending => None,
+            }
+        }
+
+        #[test]
+        fn commits_on_success() {
+            let method = Transaction::new(Bump { result: Ok(1) });
+            let mut state = 0i64;
+            let ret = futures::executor::block_on(call(&method, &mut state, true));
+            assert_eq!(ret, Some(true));
+            assert_eq!(state, 1);
+        }
+
+        #[test]
+        fn rolls_back_on_error() {
+            let method = Transaction::new(Bump { result: Err(()) });
+            let mut state = 0i64;
+            let ret = futures::executor::block_on(call(&method, &mut state, true));
+            assert_eq!(ret, Some(false));
+            assert_eq!(state, 0);
+        }
+
+        #[test]
+        fn rolls_back_when_cancelled() {
+            let method = Transaction::new(Bump { result: Ok(1) });
+            let mut state = 0i64;
+            let ret = futures::executor::block_on(call(&method, &mut state, false));
+            assert_eq!(ret, None);
+            assert_eq!(state, 0);
+        }
+    }
+}
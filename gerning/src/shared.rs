@@ -0,0 +1,482 @@
+use crate::{
+    arguments::{Arguments, ExtractOptions, FromArguments},
+    callable_fn::CallableFunc,
+    func::SharedFunc,
+    signature::{Parameters, Signature},
+    traits::{Typed, Value},
+    Callable, Error, Resultable,
+};
+
+#[cfg(feature = "async")]
+use crate::{callable_fn::CallableFuncFuture, func::AsyncSharedFunc, AsyncCallable};
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "async")]
+use futures_core::ready;
+#[cfg(feature = "async")]
+use pin_project_lite::pin_project;
+
+/// Like `Callable`, but the context is only borrowed shared,
+/// so one context can serve many concurrent calls
+pub trait SharedCallable<C, V: Value> {
+    fn signature(&self) -> Signature<V>;
+
+    fn call(&self, ctx: &C, args: Arguments<V>) -> Result<V, Error<V>>;
+}
+
+impl<F, C, U, E, V: Value> SharedCallable<C, V> for F
+where
+    F: Fn(&C, Arguments<V>) -> Result<U, E>,
+    E: Into<Error<V>>,
+    U: Into<V> + Typed<V>,
+{
+    fn signature(&self) -> Signature<V> {
+        Signature::new(Parameters::new(), U::get_type())
+    }
+
+    fn call(&self, ctx: &C, args: Arguments<V>) -> Result<V, Error<V>> {
+        (self)(ctx, args).map(|m| m.into()).map_err(|e| e.into())
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncSharedCallable<C, V: Value> {
+    type Future<'a>: Future<Output = Result<V, Error<V>>>
+    where
+        Self: 'a,
+        C: 'a;
+    fn signature(&self) -> Signature<V>;
+
+    fn call_async<'a>(&'a self, ctx: &'a C, args: Arguments<V>) -> Self::Future<'a>;
+}
+
+pub trait SharedCallableExt<C, V: Value>: SharedCallable<C, V> {
+    /// Use the callable where an exclusive context is expected
+    fn exclusive(self) -> Exclusive<Self>
+    where
+        Self: Sized,
+    {
+        Exclusive::new(self)
+    }
+}
+
+impl<T, C, V: Value> SharedCallableExt<C, V> for T where T: SharedCallable<C, V> {}
+
+/// Calls a `SharedFunc`, reusing the extraction options and signature of `CallableFunc`
+pub struct SharedCallableFunc<F, C, A, V, M = ()> {
+    callable: CallableFunc<F, C, A, V, M>,
+}
+
+impl<F: Clone, C, A, V, M> Clone for SharedCallableFunc<F, C, A, V, M> {
+    fn clone(&self) -> Self {
+        SharedCallableFunc {
+            callable: self.callable.clone(),
+        }
+    }
+}
+
+impl<F: Copy, C, A, V, M> Copy for SharedCallableFunc<F, C, A, V, M> {}
+
+unsafe impl<F: Send, C, A, V, M> Send for SharedCallableFunc<F, C, A, V, M> {}
+
+unsafe impl<F: Sync, C, A, V, M> Sync for SharedCallableFunc<F, C, A, V, M> {}
+
+impl<F, C, A, V: Value, M> SharedCallableFunc<F, C, A, V, M>
+where
+    for<'a> A: FromArguments<'a, V, M>,
+{
+    pub fn new(func: F) -> Self
+    where
+        F: SharedFunc<C, A>,
+    {
+        SharedCallableFunc {
+            callable: CallableFunc::from_func(func),
+        }
+    }
+
+    pub fn extract_options(self, options: ExtractOptions) -> Self {
+        SharedCallableFunc {
+            callable: self.callable.extract_options(options),
+        }
+    }
+
+    /// Report every failing argument at once instead of only the first
+    pub fn collect_errors(self) -> Self {
+        SharedCallableFunc {
+            callable: self.callable.collect_errors(),
+        }
+    }
+
+    /// Fail with `ArgumentError::TooMany` on arguments beyond the last parameter, instead of ignoring them
+    pub fn strict(self) -> Self {
+        SharedCallableFunc {
+            callable: self.callable.strict(),
+        }
+    }
+}
+
+impl<F, C, A, V: Value, M, U> SharedCallable<C, V> for SharedCallableFunc<F, C, A, V, M>
+where
+    for<'a> A: FromArguments<'a, V, M>,
+    for<'a> F: SharedFunc<C, <A as FromArguments<'a, V, M>>::Output, Output = U>,
+    U: Resultable,
+    U::Ok: Into<V> + Typed<V>,
+    U::Error: Into<Error<V>>,
+{
+    fn signature(&self) -> Signature<V> {
+        Signature::new(
+            <A as FromArguments<'_, V, M>>::parameters(),
+            <U::Ok as Typed<V>>::get_type(),
+        )
+    }
+
+    fn call(&self, ctx: &C, mut args: Arguments<V>) -> Result<V, Error<V>> {
+        let CallableFunc { func, options, .. } = &self.callable;
+        let args = <A as FromArguments<'_, V, M>>::from_arguments_with(&mut args, *options)
+            .map_err(|err| Error::Argument(err.into()))?;
+
+        Ok(func
+            .call(ctx, args)
+            .into_result()
+            .map_err(Into::into)?
+            .into())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, C, A, V: Value + 'static, M, U> AsyncSharedCallable<C, V>
+    for SharedCallableFunc<F, C, A, V, M>
+where
+    for<'a> A: FromArguments<'a, V, M>,
+    for<'a> F: AsyncSharedFunc<C, <A as FromArguments<'a, V, M>>::Output, Output = U>,
+    F: 'static,
+    U: Resultable,
+    U::Error: Into<Error<V>>,
+    U::Ok: Into<V> + Typed<V>,
+{
+    type Future<'a>
+        = CallableFuncFuture<
+        'a,
+        F,
+        &'a C,
+        <F as AsyncSharedFunc<C, <A as FromArguments<'a, V, M>>::Output>>::Future<'a>,
+        V,
+    >
+    where
+        C: 'a,
+        A: 'a,
+        M: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        Signature::new(
+            <A as FromArguments<'_, V, M>>::parameters(),
+            <U::Ok as Typed<V>>::get_type(),
+        )
+    }
+
+    fn call_async<'a>(&'a self, ctx: &'a C, args: Arguments<V>) -> Self::Future<'a> {
+        CallableFuncFuture::new(
+            &self.callable.func,
+            ctx,
+            args,
+            self.callable.options,
+            |func, ctx, args, options| {
+                let args = <A as FromArguments<'_, V, M>>::from_arguments_with(args, options)
+                    .map_err(|err| Error::Argument(err.into()))?;
+                Ok(func.call(ctx, args))
+            },
+        )
+    }
+}
+
+pub trait SharedFuncExt<C, A, M = ()>: SharedFunc<C, A> {
+    fn shared_callable<V: Value>(self) -> SharedCallableFunc<Self, C, A, V, M>
+    where
+        Self: Sized,
+        for<'a> A: FromArguments<'a, V, M>,
+    {
+        SharedCallableFunc::new(self)
+    }
+}
+
+impl<F, C, A, M> SharedFuncExt<C, A, M> for F where F: SharedFunc<C, A> {}
+
+/// Runs a shared-context callable where an exclusive context is expected
+#[derive(Debug, Clone, Copy)]
+pub struct Exclusive<F> {
+    callable: F,
+}
+
+impl<F> Exclusive<F> {
+    pub fn new(callable: F) -> Exclusive<F> {
+        Exclusive { callable }
+    }
+
+    pub fn into_inner(self) -> F {
+        self.callable
+    }
+}
+
+impl<F, C, V> Callable<C, V> for Exclusive<F>
+where
+    F: SharedCallable<C, V>,
+    V: Value,
+{
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call(&self, ctx: &mut C, args: Arguments<V>) -> Result<V, Error<V>> {
+        self.callable.call(ctx, args)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, C, V> AsyncCallable<C, V> for Exclusive<F>
+where
+    F: AsyncSharedCallable<C, V>,
+    V: Value,
+{
+    type Future<'a>
+        = F::Future<'a>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call_async<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Future<'a> {
+        self.callable.call_async(ctx, args)
+    }
+}
+
+/// Serves an exclusive-context callable from a shared context by locking it for each call.
+/// Implements `SharedCallable<std::sync::Mutex<C>, V>` and `AsyncSharedCallable<async_lock::Mutex<C>, V>`
+#[derive(Debug, Clone, Copy)]
+pub struct Locked<F> {
+    callable: F,
+}
+
+impl<F> Locked<F> {
+    pub fn new(callable: F) -> Locked<F> {
+        Locked { callable }
+    }
+
+    pub fn into_inner(self) -> F {
+        self.callable
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F, C, V> SharedCallable<std::sync::Mutex<C>, V> for Locked<F>
+where
+    F: Callable<C, V>,
+    V: Value,
+{
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call(&self, ctx: &std::sync::Mutex<C>, args: Arguments<V>) -> Result<V, Error<V>> {
+        let mut lock = ctx.lock().unwrap_or_else(|err| err.into_inner());
+        self.callable.call(&mut lock, args)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F, C, V> AsyncSharedCallable<async_lock::Mutex<C>, V> for Locked<F>
+where
+    F: AsyncCallable<C, V>,
+    V: Value,
+{
+    type Future<'a>
+        = LockedFuture<'a, C, CallAsync<'a, F, V>>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call_async<'a>(
+        &'a self,
+        ctx: &'a async_lock::Mutex<C>,
+        args: Arguments<V>,
+    ) -> Self::Future<'a> {
+        LockedFuture::new(
+            ctx,
+            CallAsync {
+                callable: &self.callable,
+                args,
+            },
+        )
+    }
+}
+
+/// A call to make once the shared context is locked, see `LockedFuture`
+#[cfg(feature = "async")]
+pub trait LockedCall<'a, C: 'a> {
+    type Future: Future;
+
+    fn call(self, ctx: &'a mut C) -> Self::Future;
+}
+
+/// Calls an async callable with the locked context
+#[cfg(feature = "async")]
+pub struct CallAsync<'a, F, V: Value> {
+    callable: &'a F,
+    args: Arguments<V>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, F, C, V> LockedCall<'a, C> for CallAsync<'a, F, V>
+where
+    F: AsyncCallable<C, V>,
+    C: 'a,
+    V: Value,
+{
+    type Future = F::Future<'a>;
+
+    fn call(self, ctx: &'a mut C) -> Self::Future {
+        self.callable.call_async(ctx, self.args)
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    #[project = LockedProj]
+    enum LockedState<'a, C, T>
+    where
+        C: 'a,
+        T: LockedCall<'a, C>,
+    {
+        Lock {
+            #[pin]
+            lock: async_lock::futures::Lock<'a, C>,
+            call: Option<T>,
+        },
+        // The future borrows from the guard, so it is declared, and dropped, first
+        Call {
+            #[pin]
+            future: T::Future,
+            guard: async_lock::MutexGuard<'a, C>,
+        },
+        Done,
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project! {
+    /// Locks the shared context, then runs the call while holding the lock
+    pub struct LockedFuture<'a, C, T>
+    where
+        C: 'a,
+        T: LockedCall<'a, C>,
+    {
+        #[pin]
+        state: LockedState<'a, C, T>,
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, C, T> LockedFuture<'a, C, T>
+where
+    T: LockedCall<'a, C>,
+{
+    pub(crate) fn new(ctx: &'a async_lock::Mutex<C>, call: T) -> LockedFuture<'a, C, T> {
+        LockedFuture {
+            state: LockedState::Lock {
+                lock: ctx.lock(),
+                call: Some(call),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, C, T> Future for LockedFuture<'a, C, T>
+where
+    T: LockedCall<'a, C>,
+{
+    type Output = <T::Future as Future>::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let mut this = self.as_mut().project();
+            match this.state.as_mut().project() {
+                LockedProj::Lock { lock, call } => {
+                    let mut guard = ready!(lock.poll(cx));
+                    let call = call.take().expect("call");
+
+                    // The context lives in the mutex, so it stays put when the guard is moved
+                    let ctx = &mut *guard as *mut C;
+                    let future = call.call(unsafe { &mut *ctx });
+
+                    this.state.set(LockedState::Call { future, guard });
+                }
+                LockedProj::Call { future, .. } => {
+                    let ret = ready!(future.poll(cx));
+                    this.state.set(LockedState::Done);
+                    return Poll::Ready(ret);
+                }
+                LockedProj::Done => panic!("poll after done"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{arguments::ToArguments, testing::Value};
+
+    fn add(ctx: &i64, a: i64) -> i64 {
+        ctx + a
+    }
+
+    #[test]
+    fn shared_funcs_borrow_the_context() {
+        let callable = add.shared_callable::<Value>();
+        let ctx = 1;
+        assert_eq!(
+            callable.call(&ctx, (2i64,).to_arguments()).unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(
+            callable
+                .exclusive()
+                .call(&mut 2, (2i64,).to_arguments())
+                .unwrap(),
+            Value::Int(4)
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn locked_calls_wait_for_the_lock() {
+        use futures::{executor::block_on, FutureExt};
+
+        let increment = |ctx: &mut i64| {
+            *ctx += 1;
+            let value = *ctx;
+            async move { value }
+        };
+        let callable = Locked::new(crate::FuncExt::callable::<Value>(increment));
+        let ctx = async_lock::Mutex::new(0i64);
+
+        let guard = ctx.try_lock().unwrap();
+        let mut future = core::pin::pin!(callable.call_async(&ctx, ().to_arguments()));
+        assert!(future.as_mut().now_or_never().is_none());
+        drop(guard);
+
+        assert_eq!(block_on(future).unwrap(), Value::Int(1));
+        assert_eq!(*ctx.try_lock().unwrap(), 1);
+    }
+}
@@ -3,7 +3,16 @@ use alloc::{sync::Arc, vec::Vec};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", serde(transparent))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        transparent,
+        bound(
+            serialize = "V::Type: serde::Serialize",
+            deserialize = "V::Type: serde::Deserialize<'de>"
+        )
+    )
+)]
 pub struct Parameters<V: Value>(Option<Arc<Vec<V::Type>>>);
 
 impl<T: Value> Default for Parameters<T> {
@@ -23,6 +32,11 @@ impl<T: Value> Parameters<T> {
         }
     }
 
+    /// The parameter types, or `None` if the parameters are not declared
+    pub fn as_slice(&self) -> Option<&[T::Type]> {
+        self.0.as_deref().map(|vec| vec.as_slice())
+    }
+
     pub fn get(&self, idx: usize) -> Option<&T::Type> {
         self.0.as_ref().and_then(|vec| vec.get(idx))
     }
@@ -1,11 +1,23 @@
 use super::Parameters;
-use crate::traits::Value;
+use crate::{
+    arguments::{ArgumentError, Arguments, ExtractOptions},
+    traits::Value,
+};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T::Type: serde::Serialize",
+        deserialize = "T::Type: serde::Deserialize<'de>"
+    ))
+)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Signature<T: Value> {
     params: Parameters<T>,
     return_type: Option<T::Type>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    streaming: bool,
 }
 
 impl<T: Value> Signature<T> {
@@ -13,6 +25,16 @@ impl<T: Value> Signature<T> {
         Signature {
             params,
             return_type: Some(return_type),
+            streaming: false,
+        }
+    }
+
+    /// A signature for a callable yielding a stream of `item_type`
+    pub fn stream(params: Parameters<T>, item_type: T::Type) -> Signature<T> {
+        Signature {
+            params,
+            return_type: Some(item_type),
+            streaming: true,
         }
     }
 
@@ -23,6 +45,40 @@ impl<T: Value> Signature<T> {
     pub fn return_type(&self) -> Option<&T::Type> {
         self.return_type.as_ref()
     }
+
+    /// Whether the return type is the item type of a stream
+    pub fn is_stream(&self) -> bool {
+        self.streaming
+    }
+
+    /// Check the number of `args` against the parameters, without extracting them.
+    /// Like extraction, surplus arguments are only rejected under `ExtractOptions::strict`.
+    /// Signatures without declared parameters accept any number of arguments
+    pub fn validate(
+        &self,
+        args: &Arguments<T>,
+        options: ExtractOptions,
+    ) -> Result<(), ArgumentError<T>> {
+        let Some(params) = self.params.as_slice() else {
+            return Ok(());
+        };
+
+        let found = args.len();
+        if found < params.len() {
+            let missing = ArgumentError::Missing {
+                index: found,
+                arity: found,
+            };
+            Err(missing.parameter(found))
+        } else if options.strict && found > params.len() {
+            Err(ArgumentError::TooMany {
+                expected: params.len(),
+                found,
+            })
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl<T: Value> Default for Signature<T> {
@@ -30,6 +86,56 @@ impl<T: Value> Default for Signature<T> {
         Signature {
             params: Parameters::default(),
             return_type: None,
+            streaming: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arguments::ToArguments,
+        testing::{Type, Value},
+    };
+
+    fn add() -> Signature<Value> {
+        Signature::new(
+            Parameters::build().with(Type::Int).with(Type::Int).build(),
+            Type::Int,
+        )
+    }
+
+    #[test]
+    fn surplus_arguments_are_rejected_when_strict() {
+        let strict = ExtractOptions::new().strict(true);
+        let args = (1i64, 2i64, 3i64).to_arguments();
+        assert!(add().validate(&args, ExtractOptions::new()).is_ok());
+        assert!(matches!(
+            add().validate(&args, strict),
+            Err(ArgumentError::TooMany {
+                expected: 2,
+                found: 3
+            })
+        ));
+
+        let args = (1i64, 2i64).to_arguments();
+        assert!(add().validate(&args, strict).is_ok());
+    }
+
+    #[test]
+    fn missing_arguments_are_rejected() {
+        let err = add()
+            .validate(&(1i64,).to_arguments(), ExtractOptions::new())
+            .unwrap_err();
+        assert_eq!(err.index(), Some(1));
+    }
+
+    #[test]
+    fn undeclared_parameters_accept_anything() {
+        let signature = Signature::<Value>::default();
+        let args = (1i64, "two").to_arguments();
+        let strict = ExtractOptions::new().strict(true);
+        assert!(signature.validate(&args, strict).is_ok());
+    }
+}
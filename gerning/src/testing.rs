@@ -0,0 +1,169 @@
+//! A small value type shared by the unit tests
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+};
+
+use crate::{
+    arguments::{ArgumentError, BorrowArgument},
+    Typed,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    Int(i64),
+    String(String),
+    Map(BTreeMap<String, Value>),
+    #[default]
+    Void,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Type {
+    Int,
+    String,
+    Map,
+    Void,
+}
+
+impl crate::Value for Value {
+    type Type = Type;
+
+    fn get_type(&self) -> Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::String(_) => Type::String,
+            Value::Map(_) => Type::Map,
+            Value::Void => Type::Void,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(value: &'a str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Void
+    }
+}
+
+#[cfg(feature = "service")]
+impl crate::service::NestedValue for Value {
+    fn get_field(&self, name: &str) -> Option<&Self> {
+        match self {
+            Value::Map(map) => map.get(name),
+            _ => None,
+        }
+    }
+
+    fn get_field_mut(&mut self, name: &str) -> Option<&mut Self> {
+        match self {
+            Value::Map(map) => map.get_mut(name),
+            _ => None,
+        }
+    }
+
+    fn set_field(&mut self, name: &str, value: Self) -> Result<(), Self> {
+        match self {
+            Value::Map(map) => {
+                map.insert(name.to_string(), value);
+                Ok(())
+            }
+            _ => Err(value),
+        }
+    }
+}
+
+fn invalid(expected: Type, found: &Value) -> ArgumentError<Value> {
+    ArgumentError::IvalidType {
+        expected,
+        found: crate::Value::get_type(found),
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for i64 {
+    type Error = ArgumentError<Value>;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int(i) => Ok(*i),
+            v => Err(invalid(Type::Int, v)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for String {
+    type Error = ArgumentError<Value>;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            v => Err(invalid(Type::String, v)),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ArgumentError<Value>;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s),
+            v => Err(invalid(Type::String, &v)),
+        }
+    }
+}
+
+impl<'a> From<&'a Value> for () {
+    fn from(_: &'a Value) -> Self {}
+}
+
+impl BorrowArgument<Value> for str {
+    fn borrow_argument(value: &Value) -> Result<&Self, ArgumentError<Value>> {
+        match value {
+            Value::String(s) => Ok(s),
+            v => Err(invalid(Type::String, v)),
+        }
+    }
+
+    fn parameter_type() -> Type {
+        Type::String
+    }
+}
+
+impl Typed<Value> for i64 {
+    fn get_type() -> Type {
+        Type::Int
+    }
+}
+
+impl Typed<Value> for String {
+    fn get_type() -> Type {
+        Type::String
+    }
+}
+
+impl Typed<Value> for () {
+    fn get_type() -> Type {
+        Type::Void
+    }
+}
@@ -0,0 +1,202 @@
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use pin_project_lite::pin_project;
+
+use crate::{arguments::Arguments, signature::Signature, AsyncCallable, Error, Value};
+
+#[cfg(any(feature = "smol", feature = "async-std"))]
+use alloc::boxed::Box;
+
+pub trait Timer {
+    type Sleep: Future<Output = ()>;
+
+    fn sleep(duration: Duration) -> Self::Sleep;
+}
+
+#[cfg(feature = "tokio")]
+impl Timer for crate::executor::Tokio {
+    type Sleep = tokio::time::Sleep;
+
+    fn sleep(duration: Duration) -> Self::Sleep {
+        tokio::time::sleep(duration)
+    }
+}
+
+#[cfg(feature = "smol")]
+impl Timer for crate::executor::Smol {
+    type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn sleep(duration: Duration) -> Self::Sleep {
+        Box::pin(async move {
+            smol::Timer::after(duration).await;
+        })
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl Timer for crate::executor::AsyncStd {
+    type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn sleep(duration: Duration) -> Self::Sleep {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// Fails an async callable with `Error::Timeout` if it does not complete in time
+pub struct Timeout<F, T> {
+    callable: F,
+    duration: Duration,
+    _timer: PhantomData<T>,
+}
+
+impl<F, T> Timeout<F, T> {
+    pub fn new(callable: F, duration: Duration) -> Timeout<F, T> {
+        Timeout {
+            callable,
+            duration,
+            _timer: PhantomData,
+        }
+    }
+}
+
+impl<F: Clone, T> Clone for Timeout<F, T> {
+    fn clone(&self) -> Self {
+        Timeout {
+            callable: self.callable.clone(),
+            duration: self.duration,
+            _timer: PhantomData,
+        }
+    }
+}
+
+impl<F, T, C, V> AsyncCallable<C, V> for Timeout<F, T>
+where
+    F: AsyncCallable<C, V>,
+    T: Timer,
+    V: Value,
+{
+    type Future<'a>
+        = TimeoutFuture<F::Future<'a>, T::Sleep>
+    where
+        Self: 'a,
+        C: 'a;
+
+    fn signature(&self) -> Signature<V> {
+        self.callable.signature()
+    }
+
+    fn call_async<'a>(&'a self, ctx: &'a mut C, args: Arguments<V>) -> Self::Future<'a> {
+        TimeoutFuture::new(self.callable.call_async(ctx, args), self.duration, T::sleep)
+    }
+}
+
+pin_project! {
+    /// The sleep is only created on the first poll,
+    /// since some timers panic when created outside their runtime
+    pub struct TimeoutFuture<F, S> {
+        #[pin]
+        future: F,
+        #[pin]
+        sleep: Option<S>,
+        duration: Duration,
+        make_sleep: fn(Duration) -> S,
+    }
+}
+
+impl<F, S> TimeoutFuture<F, S> {
+    pub fn new(
+        future: F,
+        duration: Duration,
+        make_sleep: fn(Duration) -> S,
+    ) -> TimeoutFuture<F, S> {
+        TimeoutFuture {
+            future,
+            sleep: None,
+            duration,
+            make_sleep,
+        }
+    }
+}
+
+impl<F, S, U, V> Future for TimeoutFuture<F, S>
+where
+    F: Future<Output = Result<U, Error<V>>>,
+    S: Future<Output = ()>,
+    V: Value,
+{
+    type Output = Result<U, Error<V>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        if let Poll::Ready(ret) = this.future.poll(cx) {
+            return Poll::Ready(ret);
+        }
+
+        if this.sleep.is_none() {
+            this.sleep.set(Some((this.make_sleep)(*this.duration)));
+        }
+
+        match this.sleep.as_pin_mut().map(|sleep| sleep.poll(cx)) {
+            Some(Poll::Ready(())) => Poll::Ready(Err(Error::Timeout)),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{arguments::ToArguments, executor::Tokio, testing::Value};
+
+    struct Sleepy(Duration);
+
+    impl AsyncCallable<(), Value> for Sleepy {
+        type Future<'a> =
+            Pin<alloc::boxed::Box<dyn Future<Output = Result<Value, Error<Value>>> + 'a>>;
+
+        fn signature(&self) -> Signature<Value> {
+            Signature::new(
+                crate::signature::Parameters::new(),
+                crate::testing::Type::Void,
+            )
+        }
+
+        fn call_async<'a>(&'a self, _ctx: &'a mut (), _args: Arguments<Value>) -> Self::Future<'a> {
+            let duration = self.0;
+            alloc::boxed::Box::pin(async move {
+                tokio::time::sleep(duration).await;
+                Ok(Value::Void)
+            })
+        }
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn futures_can_be_created_outside_the_runtime() {
+        let callable = Timeout::<_, Tokio>::new(Sleepy(Duration::ZERO), Duration::from_secs(1));
+        let mut ctx = ();
+        let future = callable.call_async(&mut ctx, ().to_arguments());
+        assert_eq!(runtime().block_on(future).unwrap(), Value::Void);
+    }
+
+    #[test]
+    fn slow_calls_time_out() {
+        let callable =
+            Timeout::<_, Tokio>::new(Sleepy(Duration::from_secs(1)), Duration::from_millis(10));
+        let mut ctx = ();
+        let future = callable.call_async(&mut ctx, ().to_arguments());
+        assert!(matches!(runtime().block_on(future), Err(Error::Timeout)));
+    }
+}
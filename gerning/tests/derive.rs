@@ -0,0 +1,176 @@
+use gerning::{
+    arguments::ArgumentError,
+    service::{State, StateField, StateFields},
+    Error, Frame, Typed,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    String(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Int,
+    String,
+}
+
+impl gerning::Value for Value {
+    type Type = Type;
+
+    fn get_type(&self) -> Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::String(_) => Type::String,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = ();
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int(i) => Ok(i),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ();
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Typed<Value> for i64 {
+    fn get_type() -> Type {
+        Type::Int
+    }
+}
+
+impl Typed<Value> for String {
+    fn get_type() -> Type {
+        Type::String
+    }
+}
+
+#[derive(Debug, Default, gerning::service::State)]
+struct Account {
+    balance: i64,
+    #[state(rename = "owner")]
+    name: String,
+    #[state(skip)]
+    #[allow(dead_code)]
+    secret: Vec<u8>,
+}
+
+#[derive(Debug, Default, gerning::service::State)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[test]
+fn gets_and_sets_fields() {
+    let mut account = Account::default();
+    account.set("balance", Value::Int(10)).unwrap();
+    account
+        .set("owner", Value::String("rasmus".into()))
+        .unwrap();
+
+    assert_eq!(account.balance, 10);
+    assert_eq!(account.name, "rasmus");
+    assert_eq!(account.get("balance").unwrap(), Some(Value::Int(10)));
+    assert_eq!(
+        account.get("owner").unwrap(),
+        Some(Value::String("rasmus".into()))
+    );
+}
+
+#[test]
+fn rejects_unknown_and_hidden_fields() {
+    let mut account = Account::default();
+    for name in ["missing", "name", "secret"] {
+        assert!(matches!(account.get(name), Err(Error::UnknownField(field)) if field == name));
+        assert!(matches!(
+            account.set(name, Value::Int(1)),
+            Err(Error::UnknownField(field)) if field == name
+        ));
+    }
+}
+
+#[test]
+fn rejects_bad_types() {
+    let mut account = Account::default();
+    let err = account
+        .set("balance", Value::String("ten".into()))
+        .unwrap_err();
+    assert_eq!(
+        err.frames().collect::<Vec<_>>(),
+        [&Frame::Message("field `balance`".into())]
+    );
+    assert!(matches!(
+        err.root(),
+        Error::Argument(ArgumentError::IvalidType {
+            expected: Type::Int,
+            found: Type::String,
+        })
+    ));
+    assert_eq!(
+        err.to_string(),
+        "field `balance`: invalid type. Expected: Int, found: String"
+    );
+    assert_eq!(account.balance, 0);
+}
+
+#[test]
+fn lists_fields() {
+    assert_eq!(
+        <Account as StateFields<Value>>::fields(),
+        [
+            StateField {
+                name: "balance",
+                ty: Type::Int,
+            },
+            StateField {
+                name: "owner",
+                ty: Type::String,
+            },
+        ]
+    );
+}
+
+#[test]
+fn supports_generics() {
+    let mut wrapper = Wrapper::<i64>::default();
+    wrapper.set("inner", Value::Int(3)).unwrap();
+    assert_eq!(wrapper.get("inner").unwrap(), Some(Value::Int(3)));
+    assert_eq!(
+        <Wrapper<i64> as StateFields<Value>>::fields()[0].ty,
+        Type::Int
+    );
+}
+
+#[test]
+fn ui() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,7 @@
+#[derive(gerning::service::State)]
+enum State {
+    A,
+    B,
+}
+
+fn main() {}
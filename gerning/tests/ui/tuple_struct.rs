@@ -0,0 +1,4 @@
+#[derive(gerning::service::State)]
+struct State(i64);
+
+fn main() {}
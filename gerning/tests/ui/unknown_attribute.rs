@@ -0,0 +1,7 @@
+#[derive(gerning::service::State)]
+struct State {
+    #[state(hidden)]
+    field: i64,
+}
+
+fn main() {}